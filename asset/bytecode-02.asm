@@ -6,9 +6,11 @@ L02:
         move 0x19
         loop_end
         jump L14
+L08:
         set_position 8, 0
         set_inversion 1, 0
         jump L00
+L0E:
         set_position 8, 0
         set_inversion 1, 0
         jump L02
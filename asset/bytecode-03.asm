@@ -14,24 +14,28 @@ L09:
         move 0x12
         loop_end
         jump L09
+L0F:
         loop_begin 8
         set_sprite 0
         move 0x00
         set_sprite 1
         move 0x00
         loop_end
+L15:
         loop_begin 8
         set_sprite 0
         move 0x00
         set_sprite 1
         move 0x00
         loop_end
+L1B:
         loop_begin 8
         set_sprite 0
         move 0x00
         set_sprite 1
         move 0x00
         loop_end
+L21:
         loop_begin 8
         set_sprite 0
         move 0x00
@@ -1,3 +1,4 @@
+L00:
         set_jump_on_damage L14
         randomize_x 0xF0
 L04:
@@ -1,3 +1,4 @@
+L00:
         bcc_x L47
         set_position 240, 16
         jump L07
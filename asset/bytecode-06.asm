@@ -1,3 +1,4 @@
+L00:
         randomize_x 0xF0
         loop_begin 10
         move 0x18
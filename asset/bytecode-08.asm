@@ -1,3 +1,4 @@
+L00:
         set_sprite 2
         loop_begin 7
         move 0x07
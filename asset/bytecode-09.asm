@@ -1,3 +1,4 @@
+L00:
         set_position 60, 0
 L03:
         bcc_x L0B
@@ -14,6 +15,7 @@ L0B:
         set_sprite 1
         move 0x28
         loop_end
+L11:
         set_position 90, 0
 L14:
         bcc_x L1C
@@ -30,15 +32,21 @@ L1C:
         set_sprite 1
         move 0x27
         loop_end
+L22:
         set_position 150, 0
         jump L14
+L27:
         set_position 178, 0
         jump L03
+L2C:
         set_position 28, 0
         jump L03
+L31:
         set_position 212, 0
         jump L03
+L36:
         set_position 96, 0
         jump L03
+L3B:
         set_position 144, 0
         jump L03
@@ -1,3 +1,4 @@
+L00:
         set_position 192, 0
         randomize_x 0x30
         set_sprite 1
@@ -21,6 +22,7 @@ L06:
 L16:
         move 0x21
         jump L16
+L19:
         set_position 192, 0
         randomize_x 0x30
         set_sprite 1
@@ -45,10 +47,12 @@ L1F:
 L30:
         move 0x22
         jump L30
+L33:
         set_inversion 1, 0
         set_position 0, 0
         randomize_x 0x30
         jump L06
+L3B:
         set_inversion 1, 0
         set_position 0, 0
         randomize_x 0x30
@@ -1,3 +1,4 @@
+L00:
         bcs_x L08
         set_position 16, 0
         set_inversion 1, 0
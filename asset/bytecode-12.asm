@@ -1,3 +1,4 @@
+L00:
         bcc_x L07
         set_position 224, 16
         jump L16
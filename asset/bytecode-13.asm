@@ -1,6 +1,8 @@
+L00:
         set_position 128, 0
         randomize_x 0x30
         jump L0D
+L07:
         set_position 64, 0
         randomize_x 0x30
         set_inversion 1, 0
@@ -1,3 +1,4 @@
+L00:
         bcs_x L07
         set_position 168, 0
         jump L0A
@@ -13,6 +14,7 @@ L0A:
         loop_begin 0
         move 0x17
         loop_end
+L13:
         bcs_x L1A
         set_position 168, 0
         jump L1D
@@ -28,6 +30,7 @@ L1D:
         loop_begin 0
         move 0x19
         loop_end
+L26:
         bcs_x L2D
         set_position 168, 0
         jump L30
@@ -43,6 +46,7 @@ L30:
         loop_begin 0
         move 0x1A
         loop_end
+L39:
         bcs_x L40
         set_position 168, 0
         jump L43
@@ -1,3 +1,4 @@
+L00:
         randomize_x 0xF0
         set_jump_on_damage L10
 L04:
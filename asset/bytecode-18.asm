@@ -1,3 +1,4 @@
+L00:
         bcc_x L08
         set_position 240, 239
         set_inversion 0, 0
@@ -1,3 +1,4 @@
+L00:
         randomize_x 0xF0
         set_homing_timer 10
         loop_begin 8
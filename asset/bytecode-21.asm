@@ -17,12 +17,15 @@ L00:
         loop_begin 0
         move 0x18
         loop_end
+L12:
         set_inversion 0, 1
         set_position 232, 239
         jump L00
+L18:
         set_inversion 1, 0
         set_position 8, 0
         jump L00
+L1E:
         set_inversion 1, 1
         set_position 8, 239
         jump L00
@@ -1,3 +1,4 @@
+L00:
         bcc_x L21
         set_position 240, 0
 L05:
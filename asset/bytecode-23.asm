@@ -1,3 +1,4 @@
+L00:
         randomize_x 0xC0
 L02:
         move 0x18
@@ -8,6 +9,7 @@ L02:
         loop_begin 0
         move 0x0B
         loop_end
+L0B:
         randomize_x 0xC0
         set_position 48, 0
 L10:
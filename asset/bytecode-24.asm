@@ -36,6 +36,7 @@ L00:
 L22:
         move 0x08
         jump L22
+L25:
         set_position 40, 239
         set_inversion 1, 0
         jump L00
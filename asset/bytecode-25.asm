@@ -1,3 +1,4 @@
+L00:
         randomize_x 0x70
         set_sprite 2
         loop_begin 12
@@ -1,3 +1,4 @@
+L00:
         loop_begin 9
         set_homing_timer 1
         move 0x18
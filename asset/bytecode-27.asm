@@ -1,3 +1,4 @@
+L00:
         randomize_x 0xF0
 L02:
         set_sprite 0
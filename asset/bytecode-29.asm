@@ -1,3 +1,4 @@
+L00:
         set_position 0, 112
         set_sprite 1
 L04:
@@ -11,6 +12,7 @@ L04:
 L0B:
         move 0x05
         jump L0B
+L0E:
         set_position 240, 112
         set_inversion 1, 0
         set_sprite 0
@@ -1,7 +1,9 @@
+L00:
         set_part 1
         set_sprite 8
         set_position 80, 0
         jump L40
+L08:
         set_part 3
         set_sprite 9
         set_position 80, 16
@@ -11,22 +13,27 @@ L10:
         set_sprite 10
         set_position 96, 0
         jump L40
+L18:
         set_part 4
         set_sprite 11
         set_position 96, 16
         jump L40
+L20:
         set_part 1
         set_sprite 8
         set_position 144, 0
         jump L40
+L28:
         set_part 3
         set_sprite 9
         set_position 144, 16
         jump L40
+L30:
         set_part 2
         set_sprite 10
         set_position 160, 0
         jump L40
+L38:
         set_part 4
         set_sprite 11
         set_position 160, 16
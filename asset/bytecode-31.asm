@@ -1,3 +1,4 @@
+L00:
         set_health 255
         set_inversion 0, 0
         set_position 12, 4
@@ -21,6 +22,7 @@
         loop_end
         set_part 1
         jump L71
+L1D:
         set_health 255
         set_inversion 1, 0
         set_position 228, 4
@@ -43,6 +45,7 @@
         loop_end
         set_part 2
         jump L71
+L39:
         set_health 255
         set_inversion 0, 1
         set_position 12, 228
@@ -65,6 +68,7 @@
         loop_end
         set_part 3
         jump L71
+L55:
         set_health 255
         set_inversion 1, 1
         set_position 228, 228
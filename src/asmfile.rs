@@ -0,0 +1,191 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use starsoldier_bytecode as bytecode;
+
+/// 最近使ったファイルの最大数。
+pub const RECENT_FILES_MAX: usize = 8;
+
+/// エディタで編集中の .asm ファイル。
+///
+/// 最後に読み書きした内容を覚えておき、エディタの内容と比較して dirty を判定する。
+/// また、ファイルの更新日時を覚えておき、外部のエディタによる変更を検出する。
+#[derive(Debug, Default)]
+pub struct AsmDocument {
+    path: Option<PathBuf>,
+    saved_text: String,
+    mtime: Option<SystemTime>,
+    // 未保存の変更がある間に外部で更新されたファイルの内容 (読み直すかどうか未決定)。
+    external_text: Option<String>,
+}
+
+impl AsmDocument {
+    /// 新規 (ファイルと結び付いていない) ドキュメント。text は未変更扱いにする。
+    pub fn untitled(text: &str) -> Self {
+        Self {
+            path: None,
+            saved_text: text.to_owned(),
+            mtime: None,
+            external_text: None,
+        }
+    }
+
+    /// ファイルを読み込み、ドキュメントとその内容を返す。
+    pub fn open<P: AsRef<Path>>(path: P) -> eyre::Result<(Self, String)> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+
+        let doc = Self {
+            path: Some(path.to_owned()),
+            saved_text: text.clone(),
+            mtime: file_mtime(path),
+            external_text: None,
+        };
+
+        Ok((doc, text))
+    }
+
+    /// ファイルを読み込み、アセンブルできることを確認してから返す (ドロップ時のインポート用)。
+    pub fn import<P: AsRef<Path>>(path: P) -> eyre::Result<(Self, String)> {
+        let (doc, text) = Self::open(path)?;
+        bytecode::asm(text.as_bytes())?;
+
+        Ok((doc, text))
+    }
+
+    pub fn save(&mut self, text: &str) -> eyre::Result<()> {
+        let path = self
+            .path
+            .clone()
+            .ok_or_else(|| eyre::eyre!("no file name (use save as)"))?;
+
+        self.save_as(path, text)
+    }
+
+    pub fn save_as<P: AsRef<Path>>(&mut self, path: P, text: &str) -> eyre::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, text)?;
+
+        self.path = Some(path.to_owned());
+        self.saved_text = text.to_owned();
+        self.mtime = file_mtime(path);
+        self.external_text = None;
+
+        Ok(())
+    }
+
+    /// ファイルが外部で更新されていれば読み直し、新しい内容を返す。
+    ///
+    /// text はエディタの現在の内容。未保存の変更がある場合は上書きしないよう None を返し、
+    /// 新しい内容は `external_change` で確認できるようにしておく。
+    /// 更新日時だけを見るので、適当な間隔で呼べばよい。
+    pub fn poll_external_change(&mut self, text: &str) -> eyre::Result<Option<String>> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        let mtime = file_mtime(path);
+        if mtime.is_none() || mtime == self.mtime {
+            return Ok(None);
+        }
+        self.mtime = mtime;
+
+        let dirty = self.is_dirty(text);
+        let text = std::fs::read_to_string(path)?;
+        if text == self.saved_text {
+            return Ok(None);
+        }
+        self.saved_text = text.clone();
+
+        if dirty {
+            self.external_text = Some(text);
+            return Ok(None);
+        }
+        self.external_text = None;
+
+        Ok(Some(text))
+    }
+
+    /// 未保存の変更がある間に外部で更新されたファイルの内容。
+    pub fn external_change(&self) -> Option<&str> {
+        self.external_text.as_deref()
+    }
+
+    /// `external_change` を取り出す。読み直す場合は返り値をエディタに反映し、
+    /// 編集中の内容を残す場合は捨てる (どちらの場合もファイルの内容との差分は dirty になる)。
+    pub fn take_external_change(&mut self) -> Option<String> {
+        self.external_text.take()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn is_dirty(&self, text: &str) -> bool {
+        self.saved_text != text
+    }
+
+    /// "bytecode-01.asm*" のような表示名 (未保存の変更があれば * を付ける)。
+    pub fn title(&self, text: &str) -> String {
+        let name = self
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(|| "(untitled)".into(), |name| name.to_string_lossy());
+        let mark = if self.is_dirty(text) { "*" } else { "" };
+
+        format!("{}{}", name, mark)
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// 最近使ったファイルの一覧 (新しい順)。1 行 1 パスのテキストファイルに保存する。
+#[derive(Debug, Default)]
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// ファイルがなければ空の一覧を返す。
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let paths = std::fs::read_to_string(path)
+            .map(|s| {
+                s.lines()
+                    .filter(|line| !line.is_empty())
+                    .take(RECENT_FILES_MAX)
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { paths }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> eyre::Result<()> {
+        let mut s = String::new();
+        for p in &self.paths {
+            s.push_str(&p.to_string_lossy());
+            s.push('\n');
+        }
+        std::fs::write(path, s)?;
+
+        Ok(())
+    }
+
+    /// 先頭に追加する。既にあれば先頭に移動する。
+    pub fn push<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_owned());
+        self.paths.truncate(RECENT_FILES_MAX);
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
@@ -1,18 +1,20 @@
-use macroquad::texture::Texture2D;
 use once_cell::sync::Lazy;
 
+use starsoldier_bytecode as bytecode;
+
+use crate::enemy::{EnemyInit, EnemySpawner};
+use crate::parse::{entrypoints_resolve, Entrypoint};
+
 macro_rules! embed_str {
     ($path:expr) => {{
         include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path))
     }};
 }
 
-macro_rules! embed_tex {
-    ($path:expr) => {{
-        Texture2D::from_file_with_format(
-            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)),
-            None,
-        )
+/// オフセット (整数) とラベル (文字列) を混ぜて書けるようにする。
+macro_rules! entrypoints {
+    ($($e:expr),* $(,)?) => {{
+        vec![$(Entrypoint::from($e)),*]
     }};
 }
 
@@ -33,7 +35,37 @@ pub struct EnemyGroupPreset {
     pub x_ini: u8,
     pub y_ini: u8,
     pub assembly: &'static str,
-    pub entrypoints: &'static [usize],
+    pub entrypoints: Vec<Entrypoint>,
+}
+
+impl EnemyGroupPreset {
+    /// アセンブリをアセンブルし、preset のパラメータで spawner を作る。
+    pub fn spawner(&self, rank: u8) -> eyre::Result<EnemySpawner> {
+        let program = bytecode::asm(self.assembly.as_bytes())?;
+        let entrypoints = entrypoints_resolve(&self.entrypoints, self.assembly)?;
+
+        let enemy_init = EnemyInit {
+            sprite_idx_base: self.sprite_idx_base,
+            program,
+            boss: self.boss,
+            difficulty: self.difficulty,
+            shot_with_rank: self.shot_with_rank,
+            accel_shot_with_rank: self.accel_shot_with_rank,
+            homing_shot_with_rank: self.homing_shot_with_rank,
+            extra_act_with_rank: self.extra_act_with_rank,
+            accel_with_rank: self.accel_with_rank,
+            rank,
+            x: self.x_ini,
+            y: self.y_ini,
+        };
+
+        Ok(EnemySpawner::new(
+            self.spawn_interval,
+            self.spawn_count,
+            &entrypoints,
+            enemy_init,
+        ))
+    }
 }
 
 pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
@@ -54,7 +86,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 238,
             assembly: embed_str!("asset/bytecode-01.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x02,
@@ -72,7 +104,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 232,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-02.asm"),
-            entrypoints: &[0x0, 0x2, 0x8, 0xE, 0x0, 0x2, 0x8, 0xE],
+            entrypoints: entrypoints!["L00", "L02", "L08", "L0E", "L00", "L02", "L08", "L0E"],
         },
         EnemyGroupPreset {
             id: 0x03,
@@ -90,7 +122,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 32,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-03.asm"),
-            entrypoints: &[0x0, 0x21, 0x1B, 0x15, 0xF],
+            entrypoints: entrypoints!["L00", "L21", "L1B", "L15", "L0F"],
         },
         EnemyGroupPreset {
             id: 0x04,
@@ -108,7 +140,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 240,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-04.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x05,
@@ -126,7 +158,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 238,
             assembly: embed_str!("asset/bytecode-05.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x06,
@@ -144,7 +176,9 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 168,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-06.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints![
+                "L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"
+            ],
         },
         EnemyGroupPreset {
             id: 0x07,
@@ -162,7 +196,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-07.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x08,
@@ -180,7 +214,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 32,
             assembly: embed_str!("asset/bytecode-08.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x09,
@@ -198,9 +232,9 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-09.asm"),
-            entrypoints: &[
-                0x0, 0x22, 0x11, 0x27, 0x31, 0x3B, 0x36, 0x2C, 0x0, 0x36, 0x11, 0x3B, 0x31, 0x27,
-                0x22, 0x2C,
+            entrypoints: entrypoints![
+                "L00", "L22", "L11", "L27", "L31", "L3B", "L36", "L2C", "L00", "L36", "L11", "L3B",
+                "L31", "L27", "L22", "L2C"
             ],
         },
         EnemyGroupPreset {
@@ -219,7 +253,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 224,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-10.asm"),
-            entrypoints: &[0x0, 0x19, 0x33, 0x3B, 0x0, 0x19, 0x33, 0x3B],
+            entrypoints: entrypoints!["L00", "L19", "L33", "L3B", "L00", "L19", "L33", "L3B"],
         },
         EnemyGroupPreset {
             id: 0x0B,
@@ -237,7 +271,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-11.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x0C,
@@ -255,7 +289,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-12.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x0D,
@@ -273,7 +307,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-13.asm"),
-            entrypoints: &[0x0, 0x7, 0x0, 0x7, 0x0, 0x7, 0x0, 0x7],
+            entrypoints: entrypoints!["L00", "L07", "L00", "L07", "L00", "L07", "L00", "L07"],
         },
         EnemyGroupPreset {
             id: 0x0E,
@@ -291,7 +325,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-14.asm"),
-            entrypoints: &[0x0, 0x39, 0x13, 0x26, 0x0, 0x39, 0x13, 0x26],
+            entrypoints: entrypoints!["L00", "L39", "L13", "L26", "L00", "L39", "L13", "L26"],
         },
         EnemyGroupPreset {
             id: 0x0F,
@@ -309,7 +343,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-15.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x10,
@@ -327,7 +361,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-16.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x12,
@@ -345,7 +379,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-18.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x13,
@@ -363,7 +397,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-19.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x15,
@@ -381,7 +415,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 232,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-21.asm"),
-            entrypoints: &[0x0, 0x0, 0x12, 0x12, 0x18, 0x18, 0x1E, 0x1E],
+            entrypoints: entrypoints!["L00", "L00", "L12", "L12", "L18", "L18", "L1E", "L1E"],
         },
         EnemyGroupPreset {
             id: 0x16,
@@ -399,7 +433,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 120,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-22.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x17,
@@ -417,7 +451,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 224,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-23.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0xB, 0xB, 0xB, 0xB],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L0B", "L0B", "L0B", "L0B"],
         },
         EnemyGroupPreset {
             id: 0x18,
@@ -435,7 +469,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 200,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-24.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x25, 0x25, 0x25, 0x25],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L25", "L25", "L25", "L25"],
         },
         EnemyGroupPreset {
             id: 0x19,
@@ -453,7 +487,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-25.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x1A,
@@ -471,7 +505,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 208,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-26.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x1B,
@@ -489,7 +523,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-27.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x1C,
@@ -507,7 +541,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 224,
             y_ini: 0,
             assembly: embed_str!("asset/bytecode-28.asm"),
-            entrypoints: &[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+            entrypoints: entrypoints!["L00", "L00", "L00", "L00", "L00", "L00", "L00", "L00"],
         },
         EnemyGroupPreset {
             id: 0x1D,
@@ -525,7 +559,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-29.asm"),
-            entrypoints: &[0x0, 0xE],
+            entrypoints: entrypoints!["L00", "L0E"],
         },
         EnemyGroupPreset {
             id: 0x1E,
@@ -543,7 +577,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-30.asm"),
-            entrypoints: &[0x18, 0x38, 0x10, 0x30, 0x8, 0x28, 0x0, 0x20],
+            entrypoints: entrypoints!["L18", "L38", "L10", "L30", "L08", "L28", "L00", "L20"],
         },
         EnemyGroupPreset {
             id: 0x1F,
@@ -561,158 +595,7 @@ pub static ENEMY_GROUP_PRESETS: Lazy<[EnemyGroupPreset; 29]> = Lazy::new(|| {
             x_ini: 0,
             y_ini: 239,
             assembly: embed_str!("asset/bytecode-31.asm"),
-            entrypoints: &[0x55, 0x39, 0x1D, 0x0],
+            entrypoints: entrypoints!["L55", "L39", "L1D", "L00"],
         },
     ]
 });
-
-pub static METASPRITES: Lazy<[Texture2D; 0x90]> = Lazy::new(|| {
-    [
-        // {{{
-        embed_tex!("asset/metasprite-000.png"),
-        embed_tex!("asset/metasprite-001.png"),
-        embed_tex!("asset/metasprite-002.png"),
-        embed_tex!("asset/metasprite-003.png"),
-        embed_tex!("asset/metasprite-004.png"),
-        embed_tex!("asset/metasprite-005.png"),
-        embed_tex!("asset/metasprite-006.png"),
-        embed_tex!("asset/metasprite-007.png"),
-        embed_tex!("asset/metasprite-008.png"),
-        embed_tex!("asset/metasprite-009.png"),
-        embed_tex!("asset/metasprite-010.png"),
-        embed_tex!("asset/metasprite-011.png"),
-        embed_tex!("asset/metasprite-012.png"),
-        embed_tex!("asset/metasprite-013.png"),
-        embed_tex!("asset/metasprite-014.png"),
-        embed_tex!("asset/metasprite-015.png"),
-        embed_tex!("asset/metasprite-016.png"),
-        embed_tex!("asset/metasprite-017.png"),
-        embed_tex!("asset/metasprite-018.png"),
-        embed_tex!("asset/metasprite-019.png"),
-        embed_tex!("asset/metasprite-020.png"),
-        embed_tex!("asset/metasprite-021.png"),
-        embed_tex!("asset/metasprite-022.png"),
-        embed_tex!("asset/metasprite-023.png"),
-        embed_tex!("asset/metasprite-024.png"),
-        embed_tex!("asset/metasprite-025.png"),
-        embed_tex!("asset/metasprite-026.png"),
-        embed_tex!("asset/metasprite-027.png"),
-        embed_tex!("asset/metasprite-028.png"),
-        embed_tex!("asset/metasprite-029.png"),
-        embed_tex!("asset/metasprite-030.png"),
-        embed_tex!("asset/metasprite-031.png"),
-        embed_tex!("asset/metasprite-032.png"),
-        embed_tex!("asset/metasprite-033.png"),
-        embed_tex!("asset/metasprite-034.png"),
-        embed_tex!("asset/metasprite-035.png"),
-        embed_tex!("asset/metasprite-036.png"),
-        embed_tex!("asset/metasprite-037.png"),
-        embed_tex!("asset/metasprite-038.png"),
-        embed_tex!("asset/metasprite-039.png"),
-        embed_tex!("asset/metasprite-040.png"),
-        embed_tex!("asset/metasprite-041.png"),
-        embed_tex!("asset/metasprite-042.png"),
-        embed_tex!("asset/metasprite-043.png"),
-        embed_tex!("asset/metasprite-044.png"),
-        embed_tex!("asset/metasprite-045.png"),
-        embed_tex!("asset/metasprite-046.png"),
-        embed_tex!("asset/metasprite-047.png"),
-        embed_tex!("asset/metasprite-048.png"),
-        embed_tex!("asset/metasprite-049.png"),
-        embed_tex!("asset/metasprite-050.png"),
-        embed_tex!("asset/metasprite-051.png"),
-        embed_tex!("asset/metasprite-052.png"),
-        embed_tex!("asset/metasprite-053.png"),
-        embed_tex!("asset/metasprite-054.png"),
-        embed_tex!("asset/metasprite-055.png"),
-        embed_tex!("asset/metasprite-056.png"),
-        embed_tex!("asset/metasprite-057.png"),
-        embed_tex!("asset/metasprite-058.png"),
-        embed_tex!("asset/metasprite-059.png"),
-        embed_tex!("asset/metasprite-060.png"),
-        embed_tex!("asset/metasprite-061.png"),
-        embed_tex!("asset/metasprite-062.png"),
-        embed_tex!("asset/metasprite-063.png"),
-        embed_tex!("asset/metasprite-064.png"),
-        embed_tex!("asset/metasprite-065.png"),
-        embed_tex!("asset/metasprite-066.png"),
-        embed_tex!("asset/metasprite-067.png"),
-        embed_tex!("asset/metasprite-068.png"),
-        embed_tex!("asset/metasprite-069.png"),
-        embed_tex!("asset/metasprite-070.png"),
-        embed_tex!("asset/metasprite-071.png"),
-        embed_tex!("asset/metasprite-072.png"),
-        embed_tex!("asset/metasprite-073.png"),
-        embed_tex!("asset/metasprite-074.png"),
-        embed_tex!("asset/metasprite-075.png"),
-        embed_tex!("asset/metasprite-076.png"),
-        embed_tex!("asset/metasprite-077.png"),
-        embed_tex!("asset/metasprite-078.png"),
-        embed_tex!("asset/metasprite-079.png"),
-        embed_tex!("asset/metasprite-080.png"),
-        embed_tex!("asset/metasprite-081.png"),
-        embed_tex!("asset/metasprite-082.png"),
-        embed_tex!("asset/metasprite-083.png"),
-        embed_tex!("asset/metasprite-084.png"),
-        embed_tex!("asset/metasprite-085.png"),
-        embed_tex!("asset/metasprite-086.png"),
-        embed_tex!("asset/metasprite-087.png"),
-        embed_tex!("asset/metasprite-088.png"),
-        embed_tex!("asset/metasprite-089.png"),
-        embed_tex!("asset/metasprite-090.png"),
-        embed_tex!("asset/metasprite-091.png"),
-        embed_tex!("asset/metasprite-092.png"),
-        embed_tex!("asset/metasprite-093.png"),
-        embed_tex!("asset/metasprite-094.png"),
-        embed_tex!("asset/metasprite-095.png"),
-        embed_tex!("asset/metasprite-096.png"),
-        embed_tex!("asset/metasprite-097.png"),
-        embed_tex!("asset/metasprite-098.png"),
-        embed_tex!("asset/metasprite-099.png"),
-        embed_tex!("asset/metasprite-100.png"),
-        embed_tex!("asset/metasprite-101.png"),
-        embed_tex!("asset/metasprite-102.png"),
-        embed_tex!("asset/metasprite-103.png"),
-        embed_tex!("asset/metasprite-104.png"),
-        embed_tex!("asset/metasprite-105.png"),
-        embed_tex!("asset/metasprite-106.png"),
-        embed_tex!("asset/metasprite-107.png"),
-        embed_tex!("asset/metasprite-108.png"),
-        embed_tex!("asset/metasprite-109.png"),
-        embed_tex!("asset/metasprite-110.png"),
-        embed_tex!("asset/metasprite-111.png"),
-        embed_tex!("asset/metasprite-112.png"),
-        embed_tex!("asset/metasprite-113.png"),
-        embed_tex!("asset/metasprite-114.png"),
-        embed_tex!("asset/metasprite-115.png"),
-        embed_tex!("asset/metasprite-116.png"),
-        embed_tex!("asset/metasprite-117.png"),
-        embed_tex!("asset/metasprite-118.png"),
-        embed_tex!("asset/metasprite-119.png"),
-        embed_tex!("asset/metasprite-120.png"),
-        embed_tex!("asset/metasprite-121.png"),
-        embed_tex!("asset/metasprite-122.png"),
-        embed_tex!("asset/metasprite-123.png"),
-        embed_tex!("asset/metasprite-124.png"),
-        embed_tex!("asset/metasprite-125.png"),
-        embed_tex!("asset/metasprite-126.png"),
-        embed_tex!("asset/metasprite-127.png"),
-        embed_tex!("asset/metasprite-128.png"),
-        embed_tex!("asset/metasprite-129.png"),
-        embed_tex!("asset/metasprite-130.png"),
-        embed_tex!("asset/metasprite-131.png"),
-        embed_tex!("asset/metasprite-132.png"),
-        embed_tex!("asset/metasprite-133.png"),
-        embed_tex!("asset/metasprite-134.png"),
-        embed_tex!("asset/metasprite-135.png"),
-        embed_tex!("asset/metasprite-136.png"),
-        embed_tex!("asset/metasprite-137.png"),
-        embed_tex!("asset/metasprite-138.png"),
-        embed_tex!("asset/metasprite-139.png"),
-        embed_tex!("asset/metasprite-140.png"),
-        embed_tex!("asset/metasprite-141.png"),
-        embed_tex!("asset/metasprite-142.png"),
-        embed_tex!("asset/metasprite-143.png"),
-        // }}}
-    ]
-});
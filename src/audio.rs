@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// 曲として扱うサウンド id。
+///
+/// bytecode-31 (ボス) は出現時に `play_sound 9` を実行し、撃破後に
+/// `restore_music` で元の曲に戻すので、9 はボス曲とみなす。
+const MUSIC_SOUND_IDS: &[u8] = &[9];
+
+// NES の APU クロック (NTSC)。ノイズの周期の計算に使う。
+const CPU_CLOCK: f32 = 1_789_773.;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SoundEvent {
+    Play(u8),
+    RestoreMusic,
+}
+
+impl std::fmt::Display for SoundEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Play(id) => write!(f, "play_sound {}", id),
+            Self::RestoreMusic => write!(f, "restore_music"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SoundLogEntry {
+    pub frame: usize,
+    pub event: SoundEvent,
+}
+
+pub fn is_music(id: u8) -> bool {
+    MUSIC_SOUND_IDS.contains(&id)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Channel {
+    /// duty 比 (0.125, 0.25, 0.5, 0.75)。
+    Square(f32),
+    Triangle,
+    Noise,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Note {
+    channel: Channel,
+    /// 周波数 (Hz)。ノイズの場合は LFSR のクロック。
+    freq: f32,
+    /// 1 秒あたりの周波数の倍率 (スイープ)。
+    sweep: f32,
+    start: f32,
+    duration: f32,
+    volume: f32,
+}
+
+/// APU 風の矩形波/三角波/ノイズで合成する短い音。
+#[derive(Clone, Debug)]
+pub struct Synth {
+    notes: Vec<Note>,
+}
+
+impl Synth {
+    /// サウンド id ごとの効果音。実機の音は使わず、id から機械的に作る。
+    pub fn sound_effect(id: u8) -> Self {
+        let freq = 440. * 2_f32.powf(f32::from(id % 24) / 12.);
+
+        let notes = match id % 4 {
+            // 下降する矩形波 (被弾音風)
+            0 => vec![Note::new(Channel::Square(0.5), freq, 0., 0.15).sweep(0.1)],
+            // 上昇する矩形波 (ショット音風)
+            1 => vec![Note::new(Channel::Square(0.25), freq / 2., 0., 0.1).sweep(8.)],
+            // ノイズ (爆発音風)
+            2 => vec![Note::new(
+                Channel::Noise,
+                CPU_CLOCK / (16. + f32::from(id)),
+                0.,
+                0.3,
+            )],
+            // 三角波のアルペジオ
+            _ => (0..3)
+                .map(|i| {
+                    let f = freq * 2_f32.powf(ARPEGGIO_MAJOR[i] / 12.);
+                    Note::new(Channel::Triangle, f, 0.05 * i as f32, 0.05)
+                })
+                .collect(),
+        };
+
+        Self { notes }
+    }
+
+    /// 曲 (ループ再生する短いフレーズ)。None はステージ曲。
+    pub fn music(id: Option<u8>) -> Self {
+        const BEAT: f32 = 0.125;
+
+        let (root, intervals) = match id {
+            None => (220., &ARPEGGIO_MAJOR),
+            Some(id) => (110. * 2_f32.powf(f32::from(id % 12) / 12.), &ARPEGGIO_MINOR),
+        };
+
+        let mut notes = vec![];
+        for bar in 0..2 {
+            let bar_root = root * if bar == 0 { 1. } else { 2_f32.powf(5. / 12.) };
+            for (i, &interval) in intervals.iter().cycle().take(8).enumerate() {
+                let start = BEAT * (8 * bar + i) as f32;
+                let f = bar_root * 2_f32.powf(interval / 12.);
+                notes.push(Note::new(Channel::Square(0.125), f * 2., start, BEAT).volume(0.3));
+            }
+            notes.push(Note::new(
+                Channel::Triangle,
+                bar_root / 2.,
+                BEAT * (8 * bar) as f32,
+                BEAT * 8.,
+            ));
+        }
+
+        Self { notes }
+    }
+
+    /// [-1, 1] のモノラルサンプル列を返す。
+    pub fn render(&self) -> Vec<f32> {
+        let end = self
+            .notes
+            .iter()
+            .map(|note| note.start + note.duration)
+            .fold(0., f32::max);
+        let len = (end * SAMPLE_RATE as f32).ceil() as usize;
+
+        let mut samples = vec![0.; len];
+        for note in &self.notes {
+            note.render_into(&mut samples);
+        }
+        for sample in &mut samples {
+            *sample = sample.clamp(-1., 1.);
+        }
+
+        samples
+    }
+}
+
+const ARPEGGIO_MAJOR: [f32; 3] = [0., 4., 7.];
+const ARPEGGIO_MINOR: [f32; 3] = [0., 3., 7.];
+
+impl Note {
+    fn new(channel: Channel, freq: f32, start: f32, duration: f32) -> Self {
+        Self {
+            channel,
+            freq,
+            sweep: 1.,
+            start,
+            duration,
+            volume: 0.5,
+        }
+    }
+
+    fn sweep(self, sweep: f32) -> Self {
+        Self { sweep, ..self }
+    }
+
+    fn volume(self, volume: f32) -> Self {
+        Self { volume, ..self }
+    }
+
+    fn render_into(&self, samples: &mut [f32]) {
+        let rate = SAMPLE_RATE as f32;
+        let first = (self.start * rate) as usize;
+        let len = (self.duration * rate) as usize;
+
+        let mut phase = 0_f32;
+        let mut lfsr: u16 = 1;
+        for i in 0..len {
+            let t = i as f32 / rate;
+            let freq = self.freq * self.sweep.powf(t);
+            // 線形に減衰するエンベロープ。
+            let env = self.volume * (1. - i as f32 / len as f32);
+
+            let prev_phase = phase;
+            phase = (phase + freq / rate).fract();
+
+            let value = match self.channel {
+                Channel::Square(duty) => {
+                    if phase < duty {
+                        1.
+                    } else {
+                        -1.
+                    }
+                }
+                Channel::Triangle => {
+                    // APU の三角波は 32 段階。
+                    let step = (phase * 32.).floor();
+                    let level = if step < 16. { step } else { 31. - step };
+                    level / 7.5 - 1.
+                }
+                Channel::Noise => {
+                    if phase < prev_phase {
+                        let bit = (lfsr ^ (lfsr >> 1)) & 1;
+                        lfsr = (lfsr >> 1) | (bit << 14);
+                    }
+                    if lfsr & 1 == 0 {
+                        1.
+                    } else {
+                        -1.
+                    }
+                }
+            };
+
+            if let Some(sample) = samples.get_mut(first + i) {
+                *sample += value * env;
+            }
+        }
+    }
+}
+
+/// 16bit モノラルの WAV ファイルにする。
+pub fn wav_bytes(samples: &[f32]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+
+    let mut buf = Vec::with_capacity(44 + data_len as usize);
+    buf.extend_from_slice(b"RIFF");
+    buf.extend_from_slice(&(36 + data_len).to_le_bytes());
+    buf.extend_from_slice(b"WAVE");
+    buf.extend_from_slice(b"fmt ");
+    buf.extend_from_slice(&16_u32.to_le_bytes());
+    buf.extend_from_slice(&1_u16.to_le_bytes()); // PCM
+    buf.extend_from_slice(&1_u16.to_le_bytes()); // mono
+    buf.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    buf.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    buf.extend_from_slice(&2_u16.to_le_bytes());
+    buf.extend_from_slice(&16_u16.to_le_bytes());
+    buf.extend_from_slice(b"data");
+    buf.extend_from_slice(&data_len.to_le_bytes());
+    for &sample in samples {
+        buf.extend_from_slice(&((sample * f32::from(i16::MAX)) as i16).to_le_bytes());
+    }
+
+    buf
+}
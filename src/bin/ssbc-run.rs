@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+
+use structopt::StructOpt;
+
+use starsoldier_bytecode as bytecode;
+
+use starsoldier_bytecode_playground as playground;
+
+/// 敵グループ (またはステージ) をウィンドウなしで実行し、フレームごとの敵の状態を出力する。
+#[derive(Debug, StructOpt)]
+#[structopt(name = "ssbc-run")]
+struct Opt {
+    /// preset id. パラメータの初期値として使う。
+    #[structopt(long, parse(try_from_str = parse_int::parse), default_value = "0x01")]
+    preset: u8,
+
+    /// preset のアセンブリの代わりに使う .asm ファイル。
+    #[structopt(long, parse(from_os_str))]
+    asm: Option<PathBuf>,
+
+    /// 敵グループ 1 つの代わりにステージのスケジュール (.toml) を実行する。
+    /// preset にはユーザー preset も使える。enemy params/spawn の指定は無視する。
+    #[structopt(long, parse(from_os_str))]
+    schedule: Option<PathBuf>,
+
+    #[structopt(long, default_value = "600")]
+    frames: usize,
+
+    // enemy params
+    #[structopt(long, parse(try_from_str = parse_int::parse))]
+    sprite_base: Option<u8>,
+    #[structopt(long)]
+    boss: Option<bool>,
+    #[structopt(long, parse(try_from_str = parse_int::parse))]
+    difficulty: Option<u8>,
+    #[structopt(long)]
+    shot_with_rank: Option<bool>,
+    #[structopt(long)]
+    accel_shot_with_rank: Option<bool>,
+    #[structopt(long)]
+    homing_shot_with_rank: Option<bool>,
+    #[structopt(long)]
+    extra_act_with_rank: Option<bool>,
+    #[structopt(long)]
+    accel_with_rank: Option<bool>,
+    #[structopt(long, parse(try_from_str = parse_int::parse))]
+    x: Option<u8>,
+    #[structopt(long, parse(try_from_str = parse_int::parse))]
+    y: Option<u8>,
+
+    // spawn
+    #[structopt(long, parse(try_from_str = parse_int::parse))]
+    spawn_interval: Option<usize>,
+    #[structopt(long, parse(try_from_str = parse_int::parse))]
+    spawn_count: Option<usize>,
+    /// 例: "0x0, 0x2, 0x8", "L08, L0C, 0x1D" (ラベルも可)
+    #[structopt(long)]
+    entrypoints: Option<String>,
+
+    // env
+    #[structopt(long)]
+    second_round: bool,
+    #[structopt(long, parse(try_from_str = playground::stage_parse), default_value = "1")]
+    stage: u8,
+    #[structopt(long, parse(try_from_str = playground::rank_parse), default_value = "0")]
+    rank: u8,
+    /// ランクを撃破数/被弾/経過時間で変化させる (実機とは無関係の試験的なモデル)。
+    #[structopt(long)]
+    experimental_rank: bool,
+    #[structopt(long, parse(try_from_str = parse_int::parse), default_value = "0")]
+    rng_idx: u8,
+    /// rand の固定値。例: "0x80" (定数), "0x10, 0x80, 0xF0" (列を循環)
+    #[structopt(long, parse(try_from_str = playground::rng_mode_parse), default_value = "")]
+    rng_pin: playground::RngMode,
+    /// 開始 RNG index を全て (128 通り) 試し、軌跡の違いを要約する。
+    #[structopt(long)]
+    rng_sweep: bool,
+}
+
+fn group_spawner(opt: &Opt) -> eyre::Result<playground::EnemySpawner> {
+    let preset = playground::ENEMY_GROUP_PRESETS
+        .iter()
+        .find(|preset| preset.id == opt.preset)
+        .ok_or_else(|| eyre::eyre!("preset not found: {:#04X}", opt.preset))?;
+
+    let assembly = match &opt.asm {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => preset.assembly.to_owned(),
+    };
+    let program = bytecode::asm(assembly.as_bytes())?;
+
+    let spawn_interval = opt.spawn_interval.unwrap_or(preset.spawn_interval);
+    let spawn_count = opt.spawn_count.unwrap_or(preset.spawn_count);
+    let entrypoints = match &opt.entrypoints {
+        Some(s) => playground::entrypoints_parse(s, spawn_count)?,
+        None => {
+            eyre::ensure!(
+                spawn_count == preset.entrypoints.len(),
+                "entrypoint count mismatch"
+            );
+            preset.entrypoints.clone()
+        }
+    };
+    let entrypoints = playground::entrypoints_resolve(&entrypoints, &assembly)?;
+
+    let enemy_init = playground::EnemyInit {
+        sprite_idx_base: opt.sprite_base.unwrap_or(preset.sprite_idx_base),
+        program,
+        boss: opt.boss.unwrap_or(preset.boss),
+        difficulty: opt.difficulty.unwrap_or(preset.difficulty),
+        shot_with_rank: opt.shot_with_rank.unwrap_or(preset.shot_with_rank),
+        accel_shot_with_rank: opt
+            .accel_shot_with_rank
+            .unwrap_or(preset.accel_shot_with_rank),
+        homing_shot_with_rank: opt
+            .homing_shot_with_rank
+            .unwrap_or(preset.homing_shot_with_rank),
+        extra_act_with_rank: opt
+            .extra_act_with_rank
+            .unwrap_or(preset.extra_act_with_rank),
+        accel_with_rank: opt.accel_with_rank.unwrap_or(preset.accel_with_rank),
+        rank: opt.rank,
+        x: opt.x.unwrap_or(preset.x_ini),
+        y: opt.y.unwrap_or(preset.y_ini),
+    };
+
+    Ok(playground::EnemySpawner::new(
+        spawn_interval,
+        spawn_count,
+        &entrypoints,
+        enemy_init,
+    ))
+}
+
+fn stage_spawners(opt: &Opt, path: &Path) -> eyre::Result<Vec<playground::ScheduledSpawner>> {
+    let mut presets: Vec<_> = playground::ENEMY_GROUP_PRESETS
+        .iter()
+        .map(playground::PresetFile::from_builtin)
+        .collect();
+    for (preset_path, preset) in playground::load_user_presets(playground::user_preset_dir()) {
+        let preset =
+            preset.map_err(|e| eyre::eyre!("cannot load {}: {}", preset_path.display(), e))?;
+        presets.push(preset);
+    }
+
+    playground::StageSchedule::load(path)?.build(&presets, opt.rank)
+}
+
+fn main() -> eyre::Result<()> {
+    let opt = Opt::from_args();
+
+    let spawners = match &opt.schedule {
+        Some(path) => stage_spawners(&opt, path)?,
+        None => vec![playground::ScheduledSpawner {
+            frame: 0,
+            spawner: group_spawner(&opt)?,
+        }],
+    };
+
+    let game = playground::Game {
+        second_round: opt.second_round,
+        stage: opt.stage,
+        rng_idx: opt.rng_idx,
+        rng_mode: opt.rng_pin.clone(),
+        rank: playground::Rank::new(if opt.experimental_rank {
+            playground::RankMode::Experimental
+        } else {
+            playground::RankMode::Fixed
+        }),
+        ..Default::default()
+    };
+    if opt.rng_sweep {
+        let results = playground::rng_sweep(&game, &spawners, opt.frames);
+        println!("# {}", playground::rng_sweep_summary(&results));
+        for result in &results {
+            println!("{}", result);
+        }
+        return Ok(());
+    }
+
+    let mut sim = playground::Simulation::new(game);
+    sim.reset_stage(spawners);
+
+    println!("# frame\tspawn\tentrypoint\tx\ty\tsprite\tstate");
+    for _ in 0..opt.frames {
+        sim.step()?;
+        for enemy in sim.enemy_snapshots() {
+            println!(
+                "{}\t{}\t{:#04X}\t{}\t{}\t{:#04X}\t{:?}",
+                sim.frame_count(),
+                enemy.spawn_idx,
+                enemy.entrypoint,
+                enemy.x,
+                enemy.y,
+                enemy.sprite_index,
+                enemy.state
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+
+use structopt::StructOpt;
+
+use starsoldier_bytecode as bytecode;
+
+use starsoldier_bytecode_playground as playground;
+
+/// asset/bytecode-XX.asm を検証する。
+///
+/// 各ファイルをアセンブルし、同名の .bin (ROM から抽出したもの) があれば
+/// バイト列を比較する。さらに逆アセンブル→再アセンブルで元に戻ることを確かめる。
+#[derive(Debug, StructOpt)]
+#[structopt(name = "ssbc-verify")]
+struct Opt {
+    #[structopt(long, parse(from_os_str), default_value = "asset")]
+    asset_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    let opt = Opt::from_args();
+
+    let disassembler = playground::Disassembler::new()?;
+
+    let mut asm_paths: Vec<PathBuf> = std::fs::read_dir(&opt.asset_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "asm"))
+        .collect();
+    asm_paths.sort();
+
+    let mut failure_count = 0;
+    for asm_path in &asm_paths {
+        let name = asm_path.file_stem().unwrap().to_string_lossy();
+        match verify(&disassembler, asm_path) {
+            Ok(bin_checked) => {
+                let bin = if bin_checked { "bin ok" } else { "no bin" };
+                println!("{}: ok ({})", name, bin);
+            }
+            Err(e) => {
+                println!("{}: NG: {}", name, e);
+                failure_count += 1;
+            }
+        }
+    }
+
+    eyre::ensure!(failure_count == 0, "{} file(s) failed", failure_count);
+
+    Ok(())
+}
+
+/// .bin と比較したかどうかを返す。
+fn verify(disassembler: &playground::Disassembler, asm_path: &Path) -> eyre::Result<bool> {
+    let program = bytecode::asm(std::fs::read(asm_path)?.as_slice())?;
+
+    let bin_path = asm_path.with_extension("bin");
+    let bin_checked = bin_path.exists();
+    if bin_checked {
+        let expected = std::fs::read(&bin_path)?;
+        eyre::ensure!(
+            program == expected,
+            "differs from {}: {}",
+            bin_path.display(),
+            playground::first_diff(&expected, &program)
+        );
+    }
+
+    disassembler.roundtrip(&program)?;
+
+    Ok(bin_checked)
+}
@@ -0,0 +1,445 @@
+use std::cell::{Cell, RefCell};
+
+use starsoldier_bytecode as bytecode;
+
+use crate::calllog::GameCallKind;
+use crate::disasm::is_branch;
+use crate::enemy::EnemySpawner;
+use crate::simulation::{EnemySnapshot, Simulation};
+use crate::srcmap::{split_label, SourceMap};
+
+/// トラップの目印に使うサウンド番号。トラップを挿入したプログラムでは、この番号の
+/// `play_sound` の直後の `play_sound` のオペランドをトラップ番号とみなす。
+pub const TRAP_MARKER_SOUND: u8 = 0x0F;
+/// 同時に設定できるブレークポイントの行数 (`play_sound` のオペランドは 4 bit)。
+pub const TRAP_COUNT_MAX: usize = 0x10;
+
+/// アセンブリの行に設定するブレークポイント。
+///
+/// `Interpreter::step` は 1 フレーム分をまとめて実行し、途中の pc を外から
+/// 見る手段がない。そこで、ブレークポイントの行の命令の直前に
+/// `play_sound 15` と `play_sound <トラップ番号>` を挿入したプログラムを作り
+/// (`BreakpointSet::instrument`)、各敵の元のプログラムと並行して実行する
+/// (`Enemy::step_traps`)。トラップを挿入したプログラムは `Game` を変更せず、
+/// 元のプログラムの `Game` の呼び出しの記録をなぞる (`ReplayGame`)。
+/// 敵の動きや外に見える pc、エントリポイントは元のプログラムのままになる。
+///
+/// 挿入した `play_sound` がそのフレームの処理を終える場合などは、呼び出しや
+/// 位置が元のプログラムと食い違う。その時点でその敵のトラップは外し、
+/// `Simulation::trap_divergences` に記録する。条件はそのフレームの処理後の状態で判定する。
+#[derive(Debug)]
+pub struct Breakpoint {
+    /// 行番号 (0-based)。
+    pub line: usize,
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    fn matches(&self, line: usize, enemy: &EnemySnapshot, frame: usize) -> bool {
+        if self.line != line {
+            return false;
+        }
+
+        match &self.condition {
+            Some(cond) => cond.eval(enemy, frame),
+            None => true,
+        }
+    }
+}
+
+/// "x < 16", "spawn == 3 && y >= 100" のような条件。
+#[derive(Debug)]
+pub struct Condition {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug)]
+struct Term {
+    var: Var,
+    op: Op,
+    value: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Var {
+    X,
+    Y,
+    Spawn,
+    Sprite,
+    Frame,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Condition {
+    pub fn parse(s: &str) -> eyre::Result<Self> {
+        let terms = s
+            .split("&&")
+            .map(Term::parse)
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Ok(Self { terms })
+    }
+
+    fn eval(&self, enemy: &EnemySnapshot, frame: usize) -> bool {
+        self.terms.iter().all(|term| term.eval(enemy, frame))
+    }
+}
+
+impl Term {
+    fn parse(s: &str) -> eyre::Result<Self> {
+        // 2 文字の演算子を先に試す。
+        const OPS: &[(&str, Op)] = &[
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+
+        let (pos, token, op) = OPS
+            .iter()
+            .find_map(|&(token, op)| s.find(token).map(|pos| (pos, token, op)))
+            .ok_or_else(|| eyre::eyre!("operator not found: {}", s))?;
+
+        let var = match s[..pos].trim() {
+            "x" => Var::X,
+            "y" => Var::Y,
+            "spawn" => Var::Spawn,
+            "sprite" => Var::Sprite,
+            "frame" => Var::Frame,
+            name => eyre::bail!("unknown variable: {}", name),
+        };
+        let value = parse_int::parse::<usize>(s[pos + token.len()..].trim())?;
+
+        Ok(Self { var, op, value })
+    }
+
+    fn eval(&self, enemy: &EnemySnapshot, frame: usize) -> bool {
+        let lhs = match self.var {
+            Var::X => usize::from(enemy.x),
+            Var::Y => usize::from(enemy.y),
+            Var::Spawn => enemy.spawn_idx,
+            Var::Sprite => usize::from(enemy.sprite_index),
+            Var::Frame => frame,
+        };
+
+        match self.op {
+            Op::Lt => lhs < self.value,
+            Op::Le => lhs <= self.value,
+            Op::Gt => lhs > self.value,
+            Op::Ge => lhs >= self.value,
+            Op::Eq => lhs == self.value,
+            Op::Ne => lhs != self.value,
+        }
+    }
+}
+
+/// トラップを挿入したプログラムが元のプログラムと食い違った敵。以後その敵のトラップは外れる。
+#[derive(Clone, Copy, Debug)]
+pub struct TrapDivergence {
+    pub frame: usize,
+    pub spawn_idx: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BreakpointHit {
+    pub spawn_idx: usize,
+    pub line: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct BreakpointSet {
+    breakpoints: Vec<Breakpoint>,
+
+    // 実行中のプログラムに仕込んだトラップの行番号 (トラップ番号順)。
+    traps: Option<Vec<usize>>,
+    // 最後の instrument 以降にブレークポイントを変更したか。
+    pending: bool,
+}
+
+impl BreakpointSet {
+    /// ブレークポイントの追加/削除は、次に `instrument` したプログラムから有効になる。
+    pub fn add(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+        self.pending = true;
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        self.breakpoints.remove(idx);
+        self.pending = true;
+    }
+
+    /// 実行中のプログラムにまだ反映していない変更があるか。
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// ブレークポイントの行にトラップを挿入したプログラムを spawner に設定する。
+    ///
+    /// srcmap は spawner のプログラムのもの。トラップを挿入すると命令のアドレスがずれるので、
+    /// 分岐先を数値で書いたプログラムは扱えない (ラベルで書くこと)。
+    /// 失敗した場合、spawner のトラップは外れる。
+    pub fn instrument(
+        &mut self,
+        srcmap: &SourceMap,
+        spawner: &mut EnemySpawner,
+    ) -> eyre::Result<()> {
+        self.traps = None;
+        self.pending = false;
+        spawner.clear_trap_program();
+
+        let mut lines: Vec<usize> = self.breakpoints.iter().map(|bp| bp.line).collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines.retain(|&line| {
+            srcmap
+                .lines()
+                .get(line)
+                .is_some_and(|l| !l.addrs.is_empty())
+        });
+        if lines.is_empty() {
+            return Ok(());
+        }
+        eyre::ensure!(
+            lines.len() <= TRAP_COUNT_MAX,
+            "too many breakpoint lines (max {})",
+            TRAP_COUNT_MAX
+        );
+
+        eyre::ensure!(
+            srcmap.program() == spawner.program(),
+            "the running program does not match the source"
+        );
+        eyre::ensure!(
+            !srcmap.lines().iter().any(|line| is_trap_marker(&line.text)),
+            "play_sound {} is reserved for breakpoints",
+            TRAP_MARKER_SOUND
+        );
+        if let Some(i) = srcmap
+            .lines()
+            .iter()
+            .position(|line| has_numeric_branch(&line.text))
+        {
+            eyre::bail!(
+                "line {}: breakpoints need a label as the branch target",
+                i + 1
+            );
+        }
+
+        let mut texts = vec![];
+        let mut line_map = vec![];
+        for (i, line) in srcmap.lines().iter().enumerate() {
+            let trap = match lines.binary_search(&i) {
+                Ok(trap) => trap,
+                Err(_) => {
+                    texts.push(line.text.clone());
+                    line_map.push(i);
+                    continue;
+                }
+            };
+
+            // ラベルへの分岐でもトラップを踏むよう、ラベルの後に挿入する。
+            let (label, insn) = split_label(&line.text);
+            if let Some(label) = label {
+                texts.push(format!("{}:", label));
+                line_map.push(i);
+            }
+            texts.push(format!("play_sound {}", TRAP_MARKER_SOUND));
+            texts.push(format!("play_sound {}", trap));
+            texts.push(insn.to_owned());
+            line_map.extend([i, i, i]);
+        }
+        let trapped = SourceMap::build(&texts.join("\n"))?;
+
+        // エントリポイントの行にトラップがあれば、トラップから実行する。
+        let entrypoints = spawner
+            .entrypoints()
+            .iter()
+            .map(|&addr| {
+                let line = srcmap
+                    .line_of_addr(addr)
+                    .filter(|&line| srcmap.lines()[line].addrs.start == addr)
+                    .ok_or_else(|| eyre::eyre!("entrypoint {:#04X} is not an instruction", addr))?;
+                let i = line_map.iter().position(|&l| l == line).unwrap();
+                Ok(trapped.lines()[i].addrs.start)
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        spawner.set_trap_program(trapped.program().to_vec(), &entrypoints);
+
+        self.traps = Some(lines);
+
+        Ok(())
+    }
+
+    /// トラップなしのプログラムを実行するときに呼ぶ。
+    pub fn clear_traps(&mut self) {
+        self.traps = None;
+        self.pending = false;
+    }
+
+    /// `Simulation::step` の直後に呼ぶ。そのフレームで最初にトラップを踏み、
+    /// かつ条件を満たした敵と行を返す。
+    pub fn check(&self, sim: &Simulation) -> Option<BreakpointHit> {
+        let traps = self.traps.as_ref()?;
+        let frame = sim.frame_count().checked_sub(1)?;
+
+        let calls: Vec<_> = sim
+            .game_calls()
+            .calls()
+            .iter()
+            .rev()
+            .take_while(|call| call.frame == frame)
+            .collect();
+        calls.into_iter().rev().find_map(|call| {
+            let line = match call.kind {
+                GameCallKind::Trap { index } => *traps.get(index)?,
+                _ => return None,
+            };
+            let enemy = sim
+                .enemy_snapshots()
+                .iter()
+                .find(|enemy| enemy.spawn_idx == call.spawn_idx)?;
+            self.breakpoints
+                .iter()
+                .any(|bp| bp.matches(line, enemy, frame))
+                .then_some(BreakpointHit {
+                    spawn_idx: call.spawn_idx,
+                    line,
+                })
+        })
+    }
+}
+
+/// トラップを挿入したプログラムを、元のプログラムの `Game` の呼び出しの記録に沿って
+/// 実行するための `Game`。`Game` の状態は変えず、記録した戻り値を返す。
+pub(crate) struct ReplayGame<'a> {
+    calls: RefCell<std::slice::Iter<'a, GameCallKind>>,
+    diverged: Cell<bool>,
+
+    traps: Vec<usize>,
+    // 直前の呼び出しがトラップの目印だったか。
+    trap_pending: bool,
+}
+
+impl<'a> ReplayGame<'a> {
+    pub(crate) fn new(calls: &'a [GameCallKind]) -> Self {
+        Self {
+            calls: RefCell::new(calls.iter()),
+            diverged: Cell::new(false),
+
+            traps: vec![],
+            trap_pending: false,
+        }
+    }
+
+    /// 踏んだトラップの番号を返す。呼び出しが記録と食い違っていたら None。
+    pub(crate) fn finish(self) -> Option<Vec<usize>> {
+        let rest = self.calls.borrow().len();
+        (!self.diverged.get() && !self.trap_pending && rest == 0).then_some(self.traps)
+    }
+
+    fn next(&self) -> Option<GameCallKind> {
+        self.calls.borrow_mut().next().copied()
+    }
+
+    fn expect(&self, kind: GameCallKind) {
+        if self.next() != Some(kind) {
+            self.diverged.set(true);
+        }
+    }
+
+    fn diverge<T>(&self, value: T) -> T {
+        self.diverged.set(true);
+        value
+    }
+}
+
+impl bytecode::Game for ReplayGame<'_> {
+    fn is_second_round(&self) -> bool {
+        match self.next() {
+            Some(GameCallKind::IsSecondRound { ret }) => ret,
+            _ => self.diverge(false),
+        }
+    }
+    fn stage(&self) -> u8 {
+        match self.next() {
+            Some(GameCallKind::Stage { ret }) => ret,
+            _ => self.diverge(0),
+        }
+    }
+
+    fn hero_x(&self) -> u8 {
+        match self.next() {
+            Some(GameCallKind::HeroX { ret }) => ret,
+            _ => self.diverge(0),
+        }
+    }
+    fn hero_y(&self) -> u8 {
+        match self.next() {
+            Some(GameCallKind::HeroY { ret }) => ret,
+            _ => self.diverge(0),
+        }
+    }
+
+    fn rand(&mut self) -> u8 {
+        match self.next() {
+            Some(GameCallKind::Rand { ret, .. }) => ret,
+            _ => self.diverge(0),
+        }
+    }
+
+    fn try_shoot_aim(&mut self, x: u8, y: u8, speed_mask: u8, force_homing: bool) {
+        self.expect(GameCallKind::TryShootAim {
+            x,
+            y,
+            speed_mask,
+            force_homing,
+        });
+    }
+
+    fn restore_music(&mut self) {
+        self.expect(GameCallKind::RestoreMusic);
+    }
+    fn play_sound(&mut self, sound: u8) {
+        if self.trap_pending {
+            self.trap_pending = false;
+            self.traps.push(usize::from(sound));
+        } else if sound == TRAP_MARKER_SOUND {
+            self.trap_pending = true;
+        } else {
+            self.expect(GameCallKind::PlaySound { sound });
+        }
+    }
+}
+
+/// 分岐先を数値で書いた分岐命令か。
+fn has_numeric_branch(text: &str) -> bool {
+    let mut words = split_label(text).1.split_whitespace();
+    words.next().is_some_and(is_branch)
+        && words
+            .next()
+            .is_some_and(|operand| parse_int::parse::<usize>(operand).is_ok())
+}
+
+fn is_trap_marker(text: &str) -> bool {
+    let mut words = split_label(text).1.split_whitespace();
+    words.next() == Some("play_sound")
+        && words
+            .next()
+            .and_then(|operand| parse_int::parse::<u8>(operand).ok())
+            == Some(TRAP_MARKER_SOUND)
+}
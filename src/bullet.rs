@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const SPEED: f32 = 2.;
+
+// ホーミング弾は一定期間、一定間隔で自機を狙い直す。
+const HOMING_DURATION: u32 = 64;
+const HOMING_INTERVAL: u32 = 8;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Bullet {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+
+    speed_mask: u8,
+    homing: bool,
+    age: u32,
+}
+
+impl Bullet {
+    /// (x, y) から (target_x, target_y) に向かう弾を作る。
+    ///
+    /// `age & speed_mask == 0` となるフレームでのみ移動する。
+    pub fn new_aim(
+        x: f32,
+        y: f32,
+        target_x: f32,
+        target_y: f32,
+        speed_mask: u8,
+        homing: bool,
+    ) -> Self {
+        let (dx, dy) = aim(x, y, target_x, target_y);
+
+        Self {
+            x,
+            y,
+            dx,
+            dy,
+
+            speed_mask,
+            homing,
+            age: 0,
+        }
+    }
+
+    pub fn step(&mut self, target_x: f32, target_y: f32) {
+        if self.homing && self.age < HOMING_DURATION && self.age.is_multiple_of(HOMING_INTERVAL) {
+            let (dx, dy) = aim(self.x, self.y, target_x, target_y);
+            self.dx = dx;
+            self.dy = dy;
+        }
+
+        if self.age & u32::from(self.speed_mask) == 0 {
+            self.x += self.dx;
+            self.y += self.dy;
+        }
+
+        self.age = self.age.wrapping_add(1);
+    }
+
+    pub fn is_on_screen(&self) -> bool {
+        (0. ..f32::from(SCREEN_WIDTH)).contains(&self.x)
+            && (0. ..f32::from(SCREEN_HEIGHT)).contains(&self.y)
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn is_homing(&self) -> bool {
+        self.homing
+    }
+}
+
+fn aim(x: f32, y: f32, target_x: f32, target_y: f32) -> (f32, f32) {
+    let dx = target_x - x;
+    let dy = target_y - y;
+    let norm = dx.hypot(dy);
+
+    // 自機と重なっている場合は真下に撃つ。
+    if norm == 0. {
+        return (0., SPEED);
+    }
+
+    (SPEED * dx / norm, SPEED * dy / norm)
+}
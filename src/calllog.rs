@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::Write;
+
+use starsoldier_bytecode as bytecode;
+
+use crate::game::Game;
+use crate::rng::RNG_TABLE_LEN;
+
+/// `CallLog` が保持する呼び出しの最大数。これを超えると古いものから捨てる。
+pub const CALL_LOG_MAX: usize = 100_000;
+
+/// `GameCallKind::name` の一覧。
+const CALL_NAMES: &[&str] = &[
+    "is_second_round",
+    "stage",
+    "hero_x",
+    "hero_y",
+    "rand",
+    "try_shoot_aim",
+    "restore_music",
+    "play_sound",
+    "trap",
+];
+
+/// `bytecode::Game` の呼び出し 1 回分 (引数と戻り値)。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameCallKind {
+    IsSecondRound {
+        ret: bool,
+    },
+    Stage {
+        ret: u8,
+    },
+    HeroX {
+        ret: u8,
+    },
+    HeroY {
+        ret: u8,
+    },
+    /// rng_idx は呼び出し前の値。
+    Rand {
+        rng_idx: u8,
+        ret: u8,
+    },
+    TryShootAim {
+        x: u8,
+        y: u8,
+        speed_mask: u8,
+        force_homing: bool,
+    },
+    RestoreMusic,
+    PlaySound {
+        sound: u8,
+    },
+    /// ブレークポイントのトラップ (トラップを挿入したプログラムでの `play_sound` の組)。
+    Trap {
+        index: usize,
+    },
+}
+
+impl GameCallKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::IsSecondRound { .. } => "is_second_round",
+            Self::Stage { .. } => "stage",
+            Self::HeroX { .. } => "hero_x",
+            Self::HeroY { .. } => "hero_y",
+            Self::Rand { .. } => "rand",
+            Self::TryShootAim { .. } => "try_shoot_aim",
+            Self::RestoreMusic => "restore_music",
+            Self::PlaySound { .. } => "play_sound",
+            Self::Trap { .. } => "trap",
+        }
+    }
+
+    /// "x=16 y=32" のような引数と戻り値の表記。
+    pub fn detail(&self) -> String {
+        match *self {
+            Self::IsSecondRound { ret } => format!("-> {}", ret),
+            Self::Stage { ret } | Self::HeroX { ret } | Self::HeroY { ret } => {
+                format!("-> {}", ret)
+            }
+            Self::Rand { rng_idx, ret } => format!("idx={:#04X} -> {:#04X}", rng_idx, ret),
+            Self::TryShootAim {
+                x,
+                y,
+                speed_mask,
+                force_homing,
+            } => format!(
+                "x={} y={} speed_mask={:#04X} force_homing={}",
+                x, y, speed_mask, force_homing
+            ),
+            Self::RestoreMusic => String::new(),
+            Self::PlaySound { sound } => format!("sound={}", sound),
+            Self::Trap { index } => format!("index={}", index),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GameCall {
+    /// 呼び出し時の `Simulation::frame_count` (そのフレームの処理後に frame + 1 になる)。
+    pub frame: usize,
+    pub spawn_idx: usize,
+    pub kind: GameCallKind,
+}
+
+impl std::fmt::Display for GameCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} #{} {} {}",
+            self.frame,
+            self.spawn_idx,
+            self.kind.name(),
+            self.kind.detail()
+        )
+    }
+}
+
+/// `Game` の呼び出しの記録。最新の `CALL_LOG_MAX` 件だけを保持する。
+///
+/// 乱数の消費 (回数と引いた RNG テーブルの index) は、捨てた分も含めて集計しておく。
+#[derive(Clone, Debug, Default)]
+pub struct CallLog {
+    calls: VecDeque<GameCall>,
+    total: usize,
+
+    rand_count: usize,
+    // RNG テーブルの各 index を引いたことがあるか (1 bit ずつ)。
+    rng_consumed: u128,
+}
+
+impl CallLog {
+    pub fn push(&mut self, call: GameCall) {
+        if let GameCallKind::Rand { rng_idx, .. } = call.kind {
+            self.rand_count += 1;
+            self.rng_consumed |= 1 << (usize::from(rng_idx) % RNG_TABLE_LEN);
+        }
+
+        if self.calls.len() == CALL_LOG_MAX {
+            self.calls.pop_front();
+        }
+        self.calls.push_back(call);
+        self.total += 1;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 集計値だけを残し、個々の呼び出しを捨てたもの。
+    pub fn summary(&self) -> Self {
+        Self {
+            calls: VecDeque::new(),
+            ..*self
+        }
+    }
+
+    /// 保持している呼び出し (古い順)。
+    pub fn calls(&self) -> &VecDeque<GameCall> {
+        &self.calls
+    }
+
+    /// これまでに記録した呼び出しの数 (捨てたものも含む)。
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// `total` が start だった時点より後に記録した呼び出し。
+    pub fn since(&self, start: usize) -> impl Iterator<Item = &GameCall> {
+        let n = self.total - start;
+        self.calls.iter().skip(self.calls.len().saturating_sub(n))
+    }
+
+    /// これまでの `rand` の呼び出し回数。
+    pub fn rand_count(&self) -> usize {
+        self.rand_count
+    }
+
+    /// RNG テーブルの idx 番目を引いたことがあるか。
+    pub fn is_rng_consumed(&self, idx: usize) -> bool {
+        self.rng_consumed & (1 << (idx % RNG_TABLE_LEN)) != 0
+    }
+}
+
+/// 呼び出しログの絞り込み条件。
+///
+/// "#3 rand" のように、spawn index (`#N`) と呼び出し名を空白区切りで書く。
+/// どちらも完全一致で比較し、省略したものは全てに一致する。
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CallFilter {
+    spawn_idx: Option<usize>,
+    name: Option<&'static str>,
+}
+
+impl CallFilter {
+    pub fn parse(s: &str) -> eyre::Result<Self> {
+        let mut filter = Self::default();
+        for token in s.split_whitespace() {
+            match token.strip_prefix('#') {
+                Some(idx) => filter.spawn_idx = Some(parse_int::parse(idx)?),
+                None => {
+                    let name = CALL_NAMES
+                        .iter()
+                        .find(|&&name| name == token)
+                        .ok_or_else(|| eyre::eyre!("unknown call: {}", token))?;
+                    filter.name = Some(name);
+                }
+            }
+        }
+
+        Ok(filter)
+    }
+
+    pub fn matches(&self, call: &GameCall) -> bool {
+        self.spawn_idx.is_none_or(|idx| idx == call.spawn_idx)
+            && self.name.is_none_or(|name| name == call.kind.name())
+    }
+}
+
+/// `Game` へのインタプリタからの呼び出しを記録するラッパー。
+///
+/// `hero_x` などは `&self` で呼ばれるので、記録先は `RefCell` で持つ。
+pub struct InstrumentedGame<'a> {
+    game: &'a mut Game,
+    frame: usize,
+    spawn_idx: usize,
+    calls: RefCell<&'a mut CallLog>,
+}
+
+impl<'a> InstrumentedGame<'a> {
+    pub fn new(game: &'a mut Game, frame: usize, spawn_idx: usize, calls: &'a mut CallLog) -> Self {
+        Self {
+            game,
+            frame,
+            spawn_idx,
+            calls: RefCell::new(calls),
+        }
+    }
+
+    fn record(&self, kind: GameCallKind) {
+        self.calls.borrow_mut().push(GameCall {
+            frame: self.frame,
+            spawn_idx: self.spawn_idx,
+            kind,
+        });
+    }
+}
+
+impl bytecode::Game for InstrumentedGame<'_> {
+    fn is_second_round(&self) -> bool {
+        let ret = self.game.is_second_round();
+        self.record(GameCallKind::IsSecondRound { ret });
+        ret
+    }
+    fn stage(&self) -> u8 {
+        let ret = self.game.stage();
+        self.record(GameCallKind::Stage { ret });
+        ret
+    }
+
+    fn hero_x(&self) -> u8 {
+        let ret = self.game.hero_x();
+        self.record(GameCallKind::HeroX { ret });
+        ret
+    }
+    fn hero_y(&self) -> u8 {
+        let ret = self.game.hero_y();
+        self.record(GameCallKind::HeroY { ret });
+        ret
+    }
+
+    fn rand(&mut self) -> u8 {
+        let rng_idx = self.game.rng_idx;
+        let ret = self.game.rand();
+        self.record(GameCallKind::Rand { rng_idx, ret });
+        ret
+    }
+
+    fn try_shoot_aim(&mut self, x: u8, y: u8, speed_mask: u8, force_homing: bool) {
+        self.game.try_shoot_aim(x, y, speed_mask, force_homing);
+        self.record(GameCallKind::TryShootAim {
+            x,
+            y,
+            speed_mask,
+            force_homing,
+        });
+    }
+
+    fn restore_music(&mut self) {
+        self.game.restore_music();
+        self.record(GameCallKind::RestoreMusic);
+    }
+    fn play_sound(&mut self, sound: u8) {
+        self.game.play_sound(sound);
+        self.record(GameCallKind::PlaySound { sound });
+    }
+}
+
+/// filter に一致する呼び出しのみ返す。
+pub fn filter_calls<'a>(log: &'a CallLog, filter: &CallFilter) -> Vec<&'a GameCall> {
+    log.calls()
+        .iter()
+        .filter(|call| filter.matches(call))
+        .collect()
+}
+
+pub fn write_calls_csv<W: Write>(calls: &[&GameCall], mut wtr: W) -> std::io::Result<()> {
+    writeln!(wtr, "frame,spawn_idx,call,detail")?;
+    for call in calls {
+        writeln!(
+            wtr,
+            "{},{},{},{}",
+            call.frame,
+            call.spawn_idx,
+            call.kind.name(),
+            call.kind.detail()
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn write_calls_json<W: Write>(calls: &[&GameCall], mut wtr: W) -> std::io::Result<()> {
+    writeln!(wtr, "[")?;
+    for (i, call) in calls.iter().enumerate() {
+        let sep = if i + 1 == calls.len() { "" } else { "," };
+        writeln!(
+            wtr,
+            r#"  {{"frame":{},"spawn_idx":{},"call":"{}","detail":"{}"}}{}"#,
+            call.frame,
+            call.spawn_idx,
+            call.kind.name(),
+            call.kind.detail(),
+            sep
+        )?;
+    }
+    writeln!(wtr, "]")?;
+
+    Ok(())
+}
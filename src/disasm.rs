@@ -0,0 +1,231 @@
+use std::collections::{BTreeSet, HashMap};
+
+use starsoldier_bytecode as bytecode;
+
+/// (ニーモニック, オペランド数, 分岐命令か)
+const INSTRUCTIONS: &[(&str, usize, bool)] = &[
+    ("move", 1, false),
+    ("loop_begin", 1, false),
+    ("loop_end", 0, false),
+    ("set_sprite", 1, false),
+    ("increment_sprite", 0, false),
+    ("decrement_sprite", 0, false),
+    ("jump", 1, true),
+    ("bcc_x", 1, true),
+    ("bcs_x", 1, true),
+    ("bcc_y", 1, true),
+    ("bcs_y", 1, true),
+    ("set_jump_on_damage", 1, true),
+    ("unset_jump_on_damage", 0, false),
+    ("shoot_aim", 1, false),
+    ("set_position", 2, false),
+    ("set_sleep_timer", 1, false),
+    ("set_inversion", 2, false),
+    ("randomize_x", 1, false),
+    ("set_part", 1, false),
+    ("set_homing_timer", 1, false),
+    ("set_health", 1, false),
+    ("play_sound", 1, false),
+    ("restore_music", 0, false),
+];
+
+/// mnemonic がアドレスをオペランドにとる分岐命令か。
+pub(crate) fn is_branch(mnemonic: &str) -> bool {
+    INSTRUCTIONS
+        .iter()
+        .any(|&(name, _, branch)| branch && name == mnemonic)
+}
+
+/// オペランドの探索範囲。
+const OPERAND_MAX: usize = 0xFF;
+
+#[derive(Clone, Debug)]
+struct Insn {
+    mnemonic: &'static str,
+    operands: Vec<usize>,
+    branch: bool,
+}
+
+/// 逆アセンブラ。
+///
+/// upstream には逆アセンブラがないので、各命令をとりうる全オペランドで
+/// `bytecode::asm` にかけ、バイト列から命令への逆引き表を作る。
+/// 分岐先はラベル `LXX` (XX はアドレス) として出力する。
+#[derive(Debug)]
+pub struct Disassembler {
+    table: HashMap<Vec<u8>, Insn>,
+    max_len: usize,
+}
+
+impl Disassembler {
+    /// 逆引き表を作る。数十万回アセンブルするので、作ったら使い回すこと。
+    pub fn new() -> eyre::Result<Self> {
+        let filler = filler_instruction()?;
+
+        let mut table = HashMap::new();
+        for &(mnemonic, arity, branch) in INSTRUCTIONS {
+            let entries = if branch {
+                probe_branch(mnemonic, filler)?
+            } else {
+                probe(mnemonic, arity)
+            };
+            for (bytes, operands) in entries {
+                table.entry(bytes).or_insert(Insn {
+                    mnemonic,
+                    operands,
+                    branch,
+                });
+            }
+        }
+        let max_len = table.keys().map(Vec::len).max().unwrap_or(0);
+
+        Ok(Self { table, max_len })
+    }
+
+    /// バイトコードをアセンブリに戻す。
+    pub fn disasm(&self, program: &[u8]) -> eyre::Result<String> {
+        let mut insns = vec![];
+        let mut addr = 0;
+        while addr < program.len() {
+            let (len, insn) = self
+                .decode(&program[addr..])
+                .ok_or_else(|| eyre::eyre!("cannot decode at {:#04X}", addr))?;
+            insns.push((addr, insn));
+            addr += len;
+        }
+
+        let targets: BTreeSet<usize> = insns
+            .iter()
+            .filter(|(_, insn)| insn.branch)
+            .map(|(_, insn)| insn.operands[0])
+            .collect();
+        for &target in &targets {
+            eyre::ensure!(
+                target == program.len() || insns.iter().any(|&(addr, _)| addr == target),
+                "branch target {:#04X} is not on an instruction boundary",
+                target
+            );
+        }
+
+        let mut text = String::new();
+        for &(addr, insn) in &insns {
+            if targets.contains(&addr) {
+                text.push_str(&format!("L{:02X}:\n", addr));
+            }
+            text.push_str(&format!("        {}\n", insn_text(insn)));
+        }
+        if targets.contains(&program.len()) {
+            text.push_str(&format!("L{:02X}:\n", program.len()));
+        }
+
+        Ok(text)
+    }
+
+    /// 逆アセンブルしてから再アセンブルし、元と一致することを確かめる。
+    pub fn roundtrip(&self, program: &[u8]) -> eyre::Result<()> {
+        let text = self.disasm(program)?;
+        let reassembled = bytecode::asm(text.as_bytes())?;
+        eyre::ensure!(
+            reassembled == program,
+            "reassembled program differs: {}",
+            first_diff(program, &reassembled)
+        );
+
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<(usize, &Insn)> {
+        (1..=self.max_len.min(bytes.len()))
+            .rev()
+            .find_map(|len| self.table.get(&bytes[..len]).map(|insn| (len, insn)))
+    }
+}
+
+/// 2 つのバイト列の最初の相違点の説明。
+pub fn first_diff(expected: &[u8], actual: &[u8]) -> String {
+    match expected.iter().zip(actual).position(|(a, b)| a != b) {
+        Some(i) => format!(
+            "at {:#04X}: expected {:#04X}, got {:#04X}",
+            i, expected[i], actual[i]
+        ),
+        None => format!("length: expected {}, got {}", expected.len(), actual.len()),
+    }
+}
+
+fn insn_text(insn: &Insn) -> String {
+    if insn.branch {
+        return format!("{} L{:02X}", insn.mnemonic, insn.operands[0]);
+    }
+
+    match insn.operands.as_slice() {
+        [] => insn.mnemonic.to_owned(),
+        operands => format!("{} {}", insn.mnemonic, itertools::join(operands, ", ")),
+    }
+}
+
+fn asm_line(text: &str) -> Option<Vec<u8>> {
+    bytecode::asm(format!("        {}\n", text).as_bytes())
+        .ok()
+        .filter(|bytes| !bytes.is_empty())
+}
+
+fn probe(mnemonic: &str, arity: usize) -> Vec<(Vec<u8>, Vec<usize>)> {
+    match arity {
+        0 => asm_line(mnemonic)
+            .map(|bytes| (bytes, vec![]))
+            .into_iter()
+            .collect(),
+        1 => (0..=OPERAND_MAX)
+            .filter_map(|x| asm_line(&format!("{} {}", mnemonic, x)).map(|bytes| (bytes, vec![x])))
+            .collect(),
+        2 => {
+            // 各オペランドの有効な値を調べてから組み合わせる。
+            let xs: Vec<_> = (0..=OPERAND_MAX)
+                .filter(|x| asm_line(&format!("{} {}, 0", mnemonic, x)).is_some())
+                .collect();
+            let ys: Vec<_> = (0..=OPERAND_MAX)
+                .filter(|y| asm_line(&format!("{} 0, {}", mnemonic, y)).is_some())
+                .collect();
+            itertools::iproduct!(xs, ys)
+                .filter_map(|(x, y)| {
+                    asm_line(&format!("{} {}, {}", mnemonic, x, y)).map(|bytes| (bytes, vec![x, y]))
+                })
+                .collect()
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// 分岐先 t にラベルを置くため、1 バイト命令 (filler) を t 個並べてから分岐命令を置く。
+fn probe_branch(mnemonic: &str, filler: &str) -> eyre::Result<Vec<(Vec<u8>, Vec<usize>)>> {
+    let mut entries: Vec<(Vec<u8>, Vec<usize>)> = vec![];
+
+    for target in 0..=OPERAND_MAX {
+        let mut src = format!("        {}\n", filler).repeat(target);
+        src.push_str("T:\n");
+        src.push_str(&format!("        {} T\n", mnemonic));
+
+        let program = match bytecode::asm(src.as_bytes()) {
+            Ok(program) => program,
+            Err(_) => continue,
+        };
+        let bytes = program[target..].to_vec();
+        eyre::ensure!(
+            entries.iter().all(|(b, _)| *b != bytes),
+            "{}: branch encoding is not absolute",
+            mnemonic
+        );
+        entries.push((bytes, vec![target]));
+    }
+
+    Ok(entries)
+}
+
+fn filler_instruction() -> eyre::Result<&'static str> {
+    INSTRUCTIONS
+        .iter()
+        .filter(|&&(_, arity, _)| arity == 0)
+        .map(|&(mnemonic, _, _)| mnemonic)
+        .find(|mnemonic| asm_line(mnemonic).is_some_and(|bytes| bytes.len() == 1))
+        .ok_or_else(|| eyre::eyre!("no 1-byte instruction found"))
+}
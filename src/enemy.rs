@@ -1,17 +1,33 @@
+use serde::{Deserialize, Serialize};
+
 use starsoldier_bytecode as bytecode;
 
-use crate::game::Game;
+use crate::breakpoint::ReplayGame;
+use crate::calllog::GameCallKind;
 
-#[derive(Debug)]
+const SPRITE_SIZE: f32 = 16.;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct EnemySpawner {
     spawn_interval: usize,
     spawn_timer: usize,
     spawn_remain: usize,
     entrypoints: Vec<usize>,
+    // ブレークポイントのトラップを挿入したプログラム。保存はしない。
+    #[serde(skip)]
+    traps: Option<TrapProgram>,
 
     enemy_init: EnemyInit,
 }
 
+/// ブレークポイントのトラップを挿入したプログラム (`BreakpointSet::instrument`)。
+#[derive(Clone, Debug)]
+struct TrapProgram {
+    program: Vec<u8>,
+    /// 元のエントリポイントに対応する、挿入後のアドレス。
+    entrypoints: Vec<usize>,
+}
+
 impl EnemySpawner {
     pub fn new(
         interval: usize,
@@ -26,12 +42,14 @@ impl EnemySpawner {
             spawn_timer: interval,
             spawn_remain: count,
             entrypoints: entrypoints.to_vec(),
+            traps: None,
 
             enemy_init,
         }
     }
 
-    pub fn step(&mut self) -> Option<Enemy> {
+    /// 出現する敵は rank で初期化する (`EnemyInit::rank` は初期ランクとしてのみ使う)。
+    pub fn step(&mut self, rank: u8) -> Option<Enemy> {
         if self.spawn_remain == 0 {
             return None;
         }
@@ -41,16 +59,51 @@ impl EnemySpawner {
             return None;
         }
 
-        let pc = self.entrypoints[self.entrypoints.len() - self.spawn_remain];
+        let spawn_idx = self.entrypoints.len() - self.spawn_remain;
+        let pc = self.entrypoints[spawn_idx];
 
         self.spawn_timer = self.spawn_interval;
         self.spawn_remain -= 1;
 
-        Some(self.enemy_init.init(pc))
+        let mut enemy = self.enemy_init.init(pc, rank);
+        enemy.spawn_idx = spawn_idx;
+        enemy.shadow = self.traps.as_ref().map(|traps| {
+            self.enemy_init
+                .interpreter(&traps.program, traps.entrypoints[spawn_idx], rank)
+        });
+        Some(enemy)
+    }
+
+    pub fn entrypoints(&self) -> &[usize] {
+        &self.entrypoints
+    }
+
+    pub fn program(&self) -> &[u8] {
+        &self.enemy_init.program
+    }
+
+    /// 出現する敵に、ブレークポイントのトラップを挿入したプログラムを並行して実行させる
+    /// (`Enemy::step_traps`)。entrypoints は元のエントリポイントに対応する挿入後のアドレス。
+    pub fn set_trap_program(&mut self, program: Vec<u8>, entrypoints: &[usize]) {
+        assert_eq!(self.entrypoints.len(), entrypoints.len());
+
+        self.traps = Some(TrapProgram {
+            program,
+            entrypoints: entrypoints.to_vec(),
+        });
+    }
+
+    pub fn clear_trap_program(&mut self) {
+        self.traps = None;
+    }
+
+    /// 初期ランク。
+    pub fn rank(&self) -> u8 {
+        self.enemy_init.rank
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct EnemyInit {
     pub sprite_idx_base: u8,
 
@@ -70,47 +123,152 @@ pub struct EnemyInit {
 }
 
 impl EnemyInit {
-    pub fn init(&self, pc: usize) -> Enemy {
+    pub fn init(&self, pc: usize, rank: u8) -> Enemy {
         Enemy {
+            spawn_idx: 0,
+            entrypoint: pc,
+
             sprite_idx_base: self.sprite_idx_base,
 
-            interp: bytecode::InterpreterInit {
-                program: self.program.clone(),
-                pc,
-
-                boss: self.boss,
-                difficulty: self.difficulty,
-                shot_with_rank: self.shot_with_rank,
-                accel_shot_with_rank: self.accel_shot_with_rank,
-                homing_shot_with_rank: self.homing_shot_with_rank,
-                extra_act_with_rank: self.extra_act_with_rank,
-                accel_with_rank: self.accel_with_rank,
-                rank: self.rank,
-
-                x: self.x,
-                y: self.y,
-            }
-            .init(),
+            interp: self.interpreter(&self.program, pc, rank),
+            shadow: None,
         }
     }
+
+    fn interpreter(&self, program: &[u8], pc: usize, rank: u8) -> bytecode::Interpreter {
+        bytecode::InterpreterInit {
+            program: program.to_vec(),
+            pc,
+
+            boss: self.boss,
+            difficulty: self.difficulty,
+            shot_with_rank: self.shot_with_rank,
+            accel_shot_with_rank: self.accel_shot_with_rank,
+            homing_shot_with_rank: self.homing_shot_with_rank,
+            extra_act_with_rank: self.extra_act_with_rank,
+            accel_with_rank: self.accel_with_rank,
+            rank,
+
+            x: self.x,
+            y: self.y,
+        }
+        .init()
+    }
 }
 
 #[derive(Debug)]
 pub struct Enemy {
+    spawn_idx: usize,
+    entrypoint: usize,
+
     sprite_idx_base: u8,
 
     interp: bytecode::Interpreter,
+    // ブレークポイントのトラップを挿入したプログラムを実行するインタプリタ。
+    shadow: Option<bytecode::Interpreter>,
 }
 
 impl Enemy {
-    pub fn step(&mut self, game: &mut Game) -> bytecode::InterpretResult<()> {
+    pub fn step<G: bytecode::Game>(&mut self, game: &mut G) -> bytecode::InterpretResult<()> {
         self.interp.step(game)
     }
 
-    pub fn damage(&mut self, game: &mut Game) {
+    pub fn damage<G: bytecode::Game>(&mut self, game: &mut G) {
         self.interp.damage(game);
     }
 
+    /// spawner が何番目に出現させた敵か。
+    pub fn spawn_index(&self) -> usize {
+        self.spawn_idx
+    }
+
+    pub fn set_spawn_index(&mut self, spawn_idx: usize) {
+        self.spawn_idx = spawn_idx;
+    }
+
+    pub fn entrypoint(&self) -> usize {
+        self.entrypoint
+    }
+
+    /// ブレークポイントのトラップを挿入したプログラムを並行して実行しているか。
+    pub fn has_traps(&self) -> bool {
+        self.shadow.is_some()
+    }
+
+    /// トラップを挿入したプログラムを、直前の `step` (damage なら `damage`) での
+    /// `Game` の呼び出し calls に沿って進め、踏んだトラップの番号を返す。
+    ///
+    /// 呼び出しや進めた後の位置、スプライト、状態が元のプログラムと食い違ったら、
+    /// トラップを外して None を返す。
+    pub fn step_traps(&mut self, calls: &[GameCallKind], damage: bool) -> Option<Vec<usize>> {
+        let shadow = self.shadow.as_mut()?;
+
+        let mut game = ReplayGame::new(calls);
+        let ok = if damage {
+            shadow.damage(&mut game);
+            true
+        } else {
+            shadow.step(&mut game).is_ok()
+        };
+        let same = ok
+            && (shadow.x(), shadow.y(), shadow.sprite_index())
+                == (self.interp.x(), self.interp.y(), self.interp.sprite_index())
+            && format!("{:?}", shadow.state()) == format!("{:?}", self.interp.state());
+
+        let traps = game.finish().filter(|_| same);
+        if traps.is_none() {
+            self.shadow = None;
+        }
+        traps
+    }
+
+    pub fn interpreter(&self) -> &bytecode::Interpreter {
+        &self.interp
+    }
+
+    /// 現在の pc。
+    ///
+    /// `bytecode::Interpreter` は pc を公開していないので、`Debug` 表記の `pc` フィールドから
+    /// 読む。読めなければ None。
+    pub fn pc(&self) -> Option<usize> {
+        let dump = format!("{:#?}", self.interp);
+        dump.lines().find_map(|line| {
+            let value = line.trim().strip_prefix("pc:")?;
+            parse_int::parse::<usize>(value.trim().trim_end_matches(',')).ok()
+        })
+    }
+
+    /// インタプリタの内部状態 (ループカウンタ、タイマー、体力など) を行ごとに返す。
+    ///
+    /// プログラム本体は長いので除く。
+    pub fn debug_lines(&self) -> Vec<String> {
+        let dump = format!("{:#?}", self.interp);
+
+        let mut lines = vec![];
+        let mut skip_indent = None;
+        for line in dump.lines() {
+            let body = line.trim_start();
+            let indent = line.len() - body.len();
+
+            if let Some(n) = skip_indent {
+                if indent == n {
+                    skip_indent = None;
+                }
+                continue;
+            }
+            if body.starts_with("program:") {
+                if body.ends_with('[') {
+                    skip_indent = Some(indent);
+                }
+                continue;
+            }
+
+            lines.push(line.to_owned());
+        }
+
+        lines
+    }
+
     pub fn state(&self) -> bytecode::EnemyState {
         self.interp.state()
     }
@@ -123,6 +281,14 @@ impl Enemy {
         self.interp.y()
     }
 
+    /// 点 (x, y) がメタスプライトの矩形内にあるかどうかを返す。
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let ex = f32::from(self.x());
+        let ey = f32::from(self.y());
+
+        (ex..ex + SPRITE_SIZE).contains(&x) && (ey..ey + SPRITE_SIZE).contains(&y)
+    }
+
     pub fn sprite_index(&self) -> u8 {
         self.sprite_idx_base + self.interp.sprite_index()
     }
@@ -1,28 +1,116 @@
+use serde::{Deserialize, Serialize};
+
 use starsoldier_bytecode as bytecode;
 
-#[rustfmt::skip]
-const RNG_TABLE: [u8; 0x80] = [
-    // {{{
-    0x1D, 0x9B, 0x9C, 0x2D, 0xD0, 0x54, 0xAF, 0x3E, 0xBF, 0x91, 0x11, 0xCF, 0x75, 0x1F, 0x6D, 0x02,
-    0x19, 0x12, 0xE4, 0x2A, 0xA8, 0xB6, 0x7F, 0x86, 0xE9, 0xEE, 0x4D, 0x9C, 0xE5, 0x8E, 0xE0, 0xB8,
-    0x80, 0x6F, 0x87, 0x1A, 0xA1, 0x57, 0xA8, 0xD5, 0xB1, 0xE9, 0xA0, 0x73, 0x6C, 0x31, 0x50, 0xD2,
-    0x25, 0x92, 0xB0, 0xD9, 0x82, 0x1D, 0x66, 0xFB, 0x0D, 0x94, 0xB8, 0x9D, 0x7B, 0xB1, 0x87, 0xDA,
-    0xB1, 0x61, 0x9E, 0x3A, 0x37, 0x11, 0x6F, 0x5A, 0x1E, 0x47, 0x74, 0x95, 0x0D, 0x1A, 0x57, 0x2C,
-    0x89, 0x44, 0xED, 0x83, 0x45, 0xE0, 0x9D, 0x6A, 0xE1, 0x1B, 0x5B, 0x8C, 0xD5, 0x5B, 0xBF, 0x7A,
-    0xE3, 0xA7, 0x4D, 0xEF, 0x63, 0x5D, 0x65, 0x36, 0xAF, 0x70, 0x34, 0xE5, 0x34, 0xC7, 0xB7, 0x49,
-    0xA3, 0x7C, 0xAB, 0x2A, 0xE0, 0xFD, 0x3E, 0xDC, 0x7C, 0x68, 0x71, 0xBA, 0x20, 0x97, 0x74, 0x73,
-    // }}}
-];
-
-#[derive(Debug)]
+use crate::audio::{self, SoundEvent};
+use crate::bullet::Bullet;
+use crate::enemy::Enemy;
+use crate::hero::{Hero, HeroInput, HERO_X_INI, HERO_Y_INI};
+use crate::rank::Rank;
+use crate::rng::RngMode;
+use crate::shot::Shot;
+
+pub const SCREEN_WIDTH: u16 = 256;
+pub const SCREEN_HEIGHT: u16 = 240;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Game {
     pub second_round: bool,
     pub stage: u8,
 
-    pub hero_x: u8,
-    pub hero_y: u8,
+    pub hero: Hero,
 
     pub rng_idx: u8,
+    #[serde(default)]
+    pub rng_mode: RngMode,
+
+    #[serde(default)]
+    pub rank: Rank,
+
+    /// 再生中の曲。None はステージ曲。
+    #[serde(default)]
+    pub music: Option<u8>,
+    /// 直前の `take_sound_events` 以降に発生したサウンドイベント。
+    #[serde(skip)]
+    pub sound_events: Vec<SoundEvent>,
+
+    pub bullets: Vec<Bullet>,
+    pub shots: Vec<Shot>,
+}
+
+// 座標はスプライトの左上を指すので、弾の発射/照準には中心を使う。
+const SPRITE_CENTER_OFFSET: f32 = 8.;
+
+impl Game {
+    pub fn step_hero(&mut self, input: HeroInput) {
+        if let Some(shot) = self.hero.step(input) {
+            self.shots.push(shot);
+        }
+    }
+
+    /// 自機ショットを移動し、敵に当たったものは damage を呼んで消す。
+    ///
+    /// damage は `Enemy::damage` を (必要なら `Game` をラップして) 呼ぶこと。
+    pub fn step_shots<F>(&mut self, enemys: &mut [Enemy], mut damage: F)
+    where
+        F: FnMut(&mut Self, &mut Enemy),
+    {
+        let mut shots = std::mem::take(&mut self.shots);
+
+        for shot in &mut shots {
+            shot.step();
+        }
+        shots.retain(|shot| {
+            if !shot.is_on_screen() {
+                return false;
+            }
+            let target = enemys.iter_mut().find(|enemy| {
+                matches!(enemy.state(), bytecode::EnemyState::Alive)
+                    && enemy.contains(shot.x(), shot.y())
+            });
+            match target {
+                Some(enemy) => {
+                    damage(self, enemy);
+                    if !matches!(enemy.state(), bytecode::EnemyState::Alive) {
+                        self.rank.on_kill();
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+
+        self.shots = shots;
+    }
+
+    /// 自機と敵/敵弾の当たり判定を行う。
+    ///
+    /// 自機が被弾した場合 true を返す。
+    pub fn check_hero_collision(&mut self, enemys: &[Enemy]) -> bool {
+        let hit = enemys.iter().any(|enemy| self.hero.hits_enemy(enemy))
+            || self.bullets.iter().any(|bullet| self.hero.hits_bullet(bullet));
+
+        let died = hit && self.hero.die();
+        if died {
+            self.rank.on_death();
+        }
+
+        died
+    }
+
+    pub fn take_sound_events(&mut self) -> Vec<SoundEvent> {
+        std::mem::take(&mut self.sound_events)
+    }
+
+    pub fn step_bullets(&mut self) {
+        let target_x = f32::from(self.hero.x) + SPRITE_CENTER_OFFSET;
+        let target_y = f32::from(self.hero.y) + SPRITE_CENTER_OFFSET;
+
+        for bullet in &mut self.bullets {
+            bullet.step(target_x, target_y);
+        }
+        self.bullets.retain(Bullet::is_on_screen);
+    }
 }
 
 impl bytecode::Game for Game {
@@ -34,27 +122,38 @@ impl bytecode::Game for Game {
     }
 
     fn hero_x(&self) -> u8 {
-        self.hero_x
+        self.hero.x
     }
     fn hero_y(&self) -> u8 {
-        self.hero_y
+        self.hero.y
     }
 
     fn rand(&mut self) -> u8 {
-        let res = RNG_TABLE[usize::from(self.rng_idx & 0x7F)];
+        let res = self.rng_mode.value(self.rng_idx);
         self.rng_idx = self.rng_idx.wrapping_add(1);
         res
     }
 
-    fn try_shoot_aim(&mut self, _x: u8, _y: u8, _speed_mask: u8, _force_homing: bool) {
-        // TODO
+    fn try_shoot_aim(&mut self, x: u8, y: u8, speed_mask: u8, force_homing: bool) {
+        self.bullets.push(Bullet::new_aim(
+            f32::from(x) + SPRITE_CENTER_OFFSET,
+            f32::from(y) + SPRITE_CENTER_OFFSET,
+            f32::from(self.hero.x) + SPRITE_CENTER_OFFSET,
+            f32::from(self.hero.y) + SPRITE_CENTER_OFFSET,
+            speed_mask,
+            force_homing,
+        ));
     }
 
     fn restore_music(&mut self) {
-        // TODO
+        self.music = None;
+        self.sound_events.push(SoundEvent::RestoreMusic);
     }
-    fn play_sound(&mut self, _sound: u8) {
-        // TODO
+    fn play_sound(&mut self, sound: u8) {
+        if audio::is_music(sound) {
+            self.music = Some(sound);
+        }
+        self.sound_events.push(SoundEvent::Play(sound));
     }
 }
 
@@ -64,10 +163,18 @@ impl Default for Game {
             second_round: false,
             stage: 1,
 
-            hero_x: 120,
-            hero_y: 200,
+            hero: Hero::new(HERO_X_INI, HERO_Y_INI),
 
             rng_idx: 0,
+            rng_mode: RngMode::default(),
+
+            rank: Rank::default(),
+
+            music: None,
+            sound_events: vec![],
+
+            bullets: vec![],
+            shots: vec![],
         }
     }
 }
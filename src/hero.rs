@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bullet::Bullet;
+use crate::enemy::Enemy;
+use crate::game::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::shot::Shot;
+
+pub const HERO_SPRITE_IDX: u8 = 0x00;
+
+/// ステージ開始時の自機の位置。
+pub const HERO_X_INI: u8 = 120;
+pub const HERO_Y_INI: u8 = 200;
+
+const SPRITE_SIZE: u8 = 16;
+
+const SPEED: u8 = 2;
+
+// 自機の当たり判定はスプライトより小さい。
+const HITBOX_OFFSET: u8 = 5;
+const HITBOX_SIZE: u8 = 6;
+
+// 自機ショットの発射間隔。
+const SHOT_INTERVAL: u32 = 8;
+
+// 被弾後、この期間は点滅して無敵になる。
+const FLASH_DURATION: u32 = 60;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct HeroInput {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    pub shoot: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Hero {
+    pub x: u8,
+    pub y: u8,
+
+    shot_timer: u32,
+    flash_timer: u32,
+    death_count: u32,
+}
+
+impl Hero {
+    pub fn new(x: u8, y: u8) -> Self {
+        Self {
+            x,
+            y,
+
+            shot_timer: 0,
+            flash_timer: 0,
+            death_count: 0,
+        }
+    }
+
+    /// 入力に従って移動する。ショットを発射した場合はそれを返す。
+    pub fn step(&mut self, input: HeroInput) -> Option<Shot> {
+        const X_MAX: u8 = (SCREEN_WIDTH - SPRITE_SIZE as u16) as u8;
+        const Y_MAX: u8 = (SCREEN_HEIGHT - SPRITE_SIZE as u16) as u8;
+
+        if input.left {
+            self.x = self.x.saturating_sub(SPEED);
+        }
+        if input.right {
+            self.x = self.x.saturating_add(SPEED).min(X_MAX);
+        }
+        if input.up {
+            self.y = self.y.saturating_sub(SPEED);
+        }
+        if input.down {
+            self.y = self.y.saturating_add(SPEED).min(Y_MAX);
+        }
+
+        self.flash_timer = self.flash_timer.saturating_sub(1);
+
+        self.shot_timer = self.shot_timer.saturating_sub(1);
+        if input.shoot && self.shot_timer == 0 {
+            self.shot_timer = SHOT_INTERVAL;
+            let x = f32::from(self.x) + f32::from(SPRITE_SIZE / 2);
+            let y = f32::from(self.y);
+            return Some(Shot::new(x, y));
+        }
+
+        None
+    }
+
+    pub fn hits_enemy(&self, enemy: &Enemy) -> bool {
+        let (hx, hy) = self.hitbox_origin();
+        let (ex, ey) = (f32::from(enemy.x()), f32::from(enemy.y()));
+        let hsize = f32::from(HITBOX_SIZE);
+        let esize = f32::from(SPRITE_SIZE);
+
+        hx < ex + esize && ex < hx + hsize && hy < ey + esize && ey < hy + hsize
+    }
+
+    pub fn hits_bullet(&self, bullet: &Bullet) -> bool {
+        let (hx, hy) = self.hitbox_origin();
+        let hsize = f32::from(HITBOX_SIZE);
+
+        (hx..hx + hsize).contains(&bullet.x()) && (hy..hy + hsize).contains(&bullet.y())
+    }
+
+    /// 被弾処理を行う。点滅中は無視する。
+    ///
+    /// 実際に被弾した場合 true を返す。
+    pub fn die(&mut self) -> bool {
+        if self.is_flashing() {
+            return false;
+        }
+
+        self.flash_timer = FLASH_DURATION;
+        self.death_count += 1;
+
+        true
+    }
+
+    pub fn is_flashing(&self) -> bool {
+        self.flash_timer > 0
+    }
+
+    /// 点滅中は数フレームおきに非表示になる。
+    pub fn is_visible(&self) -> bool {
+        (self.flash_timer / 4).is_multiple_of(2)
+    }
+
+    pub fn death_count(&self) -> u32 {
+        self.death_count
+    }
+
+    fn hitbox_origin(&self) -> (f32, f32) {
+        (
+            f32::from(self.x) + f32::from(HITBOX_OFFSET),
+            f32::from(self.y) + f32::from(HITBOX_OFFSET),
+        )
+    }
+}
@@ -1,7 +1,49 @@
+mod asmfile;
 mod asset;
+mod audio;
+mod breakpoint;
+mod bullet;
+mod calllog;
+mod disasm;
 mod enemy;
 mod game;
+mod hero;
+mod metasprite;
+mod parse;
+mod playback;
+mod preset;
+mod rank;
+mod rng;
+mod savestate;
+mod shot;
+mod simulation;
+mod srcmap;
+mod stage;
+mod timeline;
+mod trace;
+mod validate;
 
+pub use crate::asmfile::*;
 pub use crate::asset::*;
+pub use crate::audio::*;
+pub use crate::breakpoint::*;
+pub use crate::bullet::*;
+pub use crate::calllog::*;
+pub use crate::disasm::*;
 pub use crate::enemy::*;
 pub use crate::game::*;
+pub use crate::hero::*;
+pub use crate::metasprite::*;
+pub use crate::parse::*;
+pub use crate::playback::*;
+pub use crate::preset::*;
+pub use crate::rank::*;
+pub use crate::rng::*;
+pub use crate::savestate::*;
+pub use crate::shot::*;
+pub use crate::simulation::*;
+pub use crate::srcmap::*;
+pub use crate::stage::*;
+pub use crate::timeline::*;
+pub use crate::trace::*;
+pub use crate::validate::*;
@@ -1,8 +1,9 @@
+use std::collections::HashMap;
+
+use macroquad::audio::{self, PlaySoundParams, Sound};
 use macroquad::prelude::*;
 use macroquad::ui::{hash, root_ui, widgets};
 
-use starsoldier_bytecode as bytecode;
-
 use starsoldier_bytecode_playground as playground;
 
 fn set_render_target_camera(rt: RenderTarget) {
@@ -18,6 +19,120 @@ fn set_render_target_camera(rt: RenderTarget) {
     });
 }
 
+// 最近使ったファイルの一覧の保存先。
+const RECENT_FILES_PATH: &str = "recent_files.txt";
+
+// サウンドログの表示件数 (最新のもの)。
+const SOUND_LOG_DISPLAY_COUNT: usize = 16;
+
+// Game 呼び出しログの表示件数 (フィルタ後の最新のもの)。
+const CALL_LOG_DISPLAY_COUNT: usize = 32;
+
+// RNG パネルに表示する消費済みの乱数の数。
+const RNG_CONSUMED_DISPLAY_COUNT: usize = 16;
+
+// 外部で編集されている asm ファイルの更新を確認する間隔 (秒)。
+const ASM_WATCH_INTERVAL: f64 = 0.5;
+
+// 最高速再生時、表示 1 フレームあたりにシミュレーションに使う時間 (秒)。
+const MAX_SPEED_TIME_BUDGET: f64 = 0.012;
+
+/// シミュレーションのサウンドログを macroquad の audio で鳴らす。
+///
+/// 音は `playground::Synth` で合成し、初回の再生時に WAV として読み込む。
+#[derive(Default)]
+struct AudioPlayer {
+    muted: bool,
+    effects: HashMap<u8, Sound>,
+    musics: HashMap<Option<u8>, Sound>,
+    // 再生中の曲 (Some(None) はステージ曲)。
+    music_playing: Option<Option<u8>>,
+    // sound_log のうち処理済みの件数。
+    played_len: usize,
+}
+
+impl AudioPlayer {
+    /// 前回以降にログに追加された効果音を鳴らし、曲をゲームの状態に合わせる。
+    async fn sync(&mut self, sim: &playground::Simulation) {
+        let log = sim.sound_log();
+        let new_entries = log.get(self.played_len..).unwrap_or_default();
+        self.played_len = log.len();
+
+        // 高速再生時に大量に鳴らさないよう、1 回の呼び出しで鳴らすのは最後の 1 つだけ。
+        let last_effect = new_entries
+            .iter()
+            .rev()
+            .find_map(|entry| match entry.event {
+                playground::SoundEvent::Play(id) if !playground::is_music(id) => Some(id),
+                _ => None,
+            });
+        if let (Some(id), false) = (last_effect, self.muted) {
+            if let Some(sound) = self.effect(id).await {
+                audio::play_sound_once(sound);
+            }
+        }
+
+        let music = if self.muted {
+            None
+        } else {
+            Some(sim.game().music)
+        };
+        if music != self.music_playing {
+            if let Some(sound) = self.music_playing.and_then(|id| self.musics.get(&id)) {
+                audio::stop_sound(*sound);
+            }
+            if let Some(id) = music {
+                if let Some(sound) = self.music(id).await {
+                    audio::play_sound(
+                        sound,
+                        PlaySoundParams {
+                            looped: true,
+                            volume: 0.5,
+                        },
+                    );
+                }
+            }
+            self.music_playing = music;
+        }
+    }
+
+    /// シーク後に呼ぶ。それまでのログは鳴らさない。
+    fn skip(&mut self, sim: &playground::Simulation) {
+        self.played_len = sim.sound_log().len();
+    }
+
+    async fn effect(&mut self, id: u8) -> Option<Sound> {
+        if let Some(&sound) = self.effects.get(&id) {
+            return Some(sound);
+        }
+
+        let sound = load_synth(&playground::Synth::sound_effect(id)).await?;
+        self.effects.insert(id, sound);
+        Some(sound)
+    }
+
+    async fn music(&mut self, id: Option<u8>) -> Option<Sound> {
+        if let Some(&sound) = self.musics.get(&id) {
+            return Some(sound);
+        }
+
+        let sound = load_synth(&playground::Synth::music(id)).await?;
+        self.musics.insert(id, sound);
+        Some(sound)
+    }
+}
+
+async fn load_synth(synth: &playground::Synth) -> Option<Sound> {
+    let wav = playground::wav_bytes(&synth.render());
+    match audio::load_sound_from_bytes(&wav).await {
+        Ok(sound) => Some(sound),
+        Err(e) => {
+            warn!("cannot load sound: {}", e);
+            None
+        }
+    }
+}
+
 fn window_conf() -> Conf {
     Conf {
         window_title: "Star Soldier bytecode playground".to_owned(),
@@ -29,127 +144,149 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() -> eyre::Result<()> {
-    let mut game = playground::Game::default();
-    let mut spawner = None;
-    let mut enemys = vec![];
+    let mut sim = playground::Simulation::new(playground::Game::default());
+    let mut tracer = playground::TraceRecorder::new();
+    let mut timeline = playground::Timeline::new(&sim);
+    let mut playback = playground::Playback::default();
+    let mut source_map: Option<playground::SourceMap> = None;
+    let mut selected_spawn_idx: Option<usize> = None;
+    let mut breakpoints = playground::BreakpointSet::default();
+    let mut breakpoint_hit: Option<playground::BreakpointHit> = None;
+    let mut breakpoint_line_str = String::new();
+    let mut breakpoint_cond_str = String::new();
 
     let screen = render_target(256, 240);
 
-    let preset_variants = make_preset_variants();
-    let preset_variants = preset_variants
-        .iter()
-        .map(AsRef::as_ref)
-        .collect::<Vec<_>>();
+    let mut preset_errors = vec![];
+    let mut presets = load_presets(&mut preset_errors);
+    let mut preset_variants = make_preset_variants(&presets);
     let mut preset_idx = Some(0);
+    let mut preset_name = "my preset".to_owned();
+    let mut preset_id_str = "0x80".to_owned();
+    let mut preset_overwrite = false;
+    let mut preset_path = "preset.toml".to_owned();
+    let mut stage_path = "stage.toml".to_owned();
 
-    let mut sprite_idx_base_str: String;
-    let mut boss: bool;
-    let mut difficulty_str: String;
-    let mut shot_with_rank: bool;
-    let mut accel_shot_with_rank: bool;
-    let mut homing_shot_with_rank: bool;
-    let mut extra_act_with_rank: bool;
-    let mut accel_with_rank: bool;
-    let mut x_ini_str: String;
-    let mut y_ini_str: String;
-    let mut assembly: String;
-    let mut spawn_interval_str: String;
-    let mut spawn_count_str: String;
-    let mut entrypoints_str: String;
-    let mut second_round = false;
-    let mut stage_str = "1".to_owned();
-    let mut rank_str = "0".to_owned();
-    let mut rng_idx_str = "0".to_owned();
+    let mut fields = playground::EditorFields::default();
+    let mut step_count_str = "60".to_owned();
+    let mut keys_to_screen = true;
+    let mut savestate_path = "savestate.json".to_owned();
+
+    let mut asm_doc: playground::AsmDocument; // load_preset! で初期化される
+    let mut asm_path = "enemy.asm".to_owned();
+    let mut recent_files = playground::RecentFiles::load(RECENT_FILES_PATH);
+    let mut dropped_paths: Vec<std::path::PathBuf> = vec![];
+    let mut audio_player = AudioPlayer::default();
+    let mut call_filter = String::new();
+    let mut rng_sweep_frames_str = "600".to_owned();
+    let mut rng_sweep: Option<playground::RngSweep> = None;
+    let mut asm_watch_time = 0.;
+    let mut build_errors = playground::BuildErrors {
+        other: preset_errors,
+        ..Default::default()
+    };
+    let mut selected_asm_error: Option<usize> = None;
+
+    // ステージや preset の読み込みエラーを、アセンブルエラーと同じ欄に表示する。
+    macro_rules! report_error {
+        ($($arg:tt)*) => {{
+            let message = format!($($arg)*);
+            warn!("{}", message);
+            build_errors.other.push(message);
+        }};
+    }
 
     macro_rules! load_preset {
         () => {{
-            let preset = &playground::ENEMY_GROUP_PRESETS[preset_idx.unwrap()];
-            sprite_idx_base_str = format!("{:#04X}", preset.sprite_idx_base);
-            boss = preset.boss;
-            difficulty_str = preset.difficulty.to_string();
-            shot_with_rank = preset.shot_with_rank;
-            accel_shot_with_rank = preset.accel_shot_with_rank;
-            homing_shot_with_rank = preset.homing_shot_with_rank;
-            extra_act_with_rank = preset.extra_act_with_rank;
-            accel_with_rank = preset.accel_with_rank;
-            x_ini_str = preset.x_ini.to_string();
-            y_ini_str = preset.y_ini.to_string();
-            assembly = preset.assembly.to_owned();
-            spawn_interval_str = preset.spawn_interval.to_string();
-            spawn_count_str = preset.spawn_count.to_string();
-            entrypoints_str = entrypoints_format(preset.entrypoints);
+            presets[preset_idx.unwrap()].apply_to_fields(&mut fields);
+            asm_doc = playground::AsmDocument::untitled(&fields.assembly);
+        }};
+    }
+
+    macro_rules! open_asm {
+        ($res:expr) => {{
+            match $res {
+                Ok((doc, text)) => {
+                    let path = doc.path().unwrap().to_owned();
+                    asm_path = path.display().to_string();
+                    remember_recent_file(&mut recent_files, &path);
+                    asm_doc = doc;
+                    fields.assembly = text;
+                }
+                Err(e) => warn!("cannot open asm: {}", e),
+            }
         }};
     }
 
-    macro_rules! build_enemy_spawner {
+    // spawner を差し替え、最初から実行する。
+    macro_rules! restart {
+        ($env:expr, $spawners:expr, $source_map:expr) => {{
+            $env.apply(sim.game_mut());
+            sim.reset_stage($spawners);
+            timeline = playground::Timeline::new(&sim);
+            tracer.clear();
+            selected_spawn_idx = None;
+            breakpoint_hit = None;
+            source_map = $source_map;
+            build_errors = playground::BuildErrors::default();
+        }};
+    }
+
+    // 現在の入力欄の内容で spawner を作り直し、最初から実行する。
+    macro_rules! play {
+        () => {{
+            match fields.build() {
+                Ok((mut spawner, env)) => {
+                    let srcmap = build_source_map(&fields.assembly);
+                    instrument_breakpoints(&mut breakpoints, srcmap.as_ref(), &mut spawner);
+                    restart!(
+                        env,
+                        vec![playground::ScheduledSpawner { frame: 0, spawner }],
+                        srcmap
+                    )
+                }
+                Err(e) => {
+                    warn!("{}", e);
+                    build_errors = e;
+                    selected_asm_error = None;
+                }
+            }
+        }};
+    }
+
+    // ステージのスケジュールを読み込み、env の入力欄の内容で最初から実行する。
+    macro_rules! play_stage {
         () => {{
-            macro_rules! try_ {
-                ($res:expr, $msg:expr) => {{
-                    if let Err(e) = $res {
-                        break Err(format!("{}: {}", $msg, e));
+            match fields.build_env() {
+                Ok(env) => match playground::StageSchedule::load(&stage_path)
+                    .and_then(|schedule| schedule.build(&presets, env.rank))
+                {
+                    // 複数のプログラムが動くのでソースとは対応付けない。
+                    Ok(spawners) => {
+                        breakpoints.clear_traps();
+                        restart!(env, spawners, None)
                     }
-                    $res.unwrap()
-                }};
+                    Err(e) => report_error!("cannot play stage: {}", e),
+                },
+                Err(e) => {
+                    warn!("{}", e);
+                    build_errors = e;
+                    selected_asm_error = None;
+                }
             }
-            loop {
-                let spawn_interval = try_!(
-                    parse_int::parse::<usize>(&spawn_interval_str),
-                    "cannot parse spawn interval"
-                );
-                let spawn_count = try_!(
-                    parse_int::parse::<usize>(&spawn_count_str),
-                    "cannot parse spawn count"
-                );
-                let entrypoints = try_!(
-                    entrypoints_parse(&entrypoints_str, spawn_count),
-                    "entrypoints error"
-                );
-                let sprite_idx_base = try_!(
-                    parse_int::parse::<u8>(&sprite_idx_base_str),
-                    "cannot parse sprite index base"
-                );
-                let difficulty = try_!(
-                    parse_int::parse::<u8>(&difficulty_str),
-                    "cannot parse difficulty"
-                );
-                let x_ini = try_!(
-                    parse_int::parse::<u8>(&x_ini_str),
-                    "cannot parse initial position x"
-                );
-                let y_ini = try_!(
-                    parse_int::parse::<u8>(&y_ini_str),
-                    "cannot parse initial position y"
-                );
-                let program = try_!(bytecode::asm(assembly.as_bytes()), "assemble failed");
-                let stage = try_!(stage_parse(&stage_str), "cannot parse stage");
-                let rank = try_!(rank_parse(&rank_str), "cannot parse rank");
-                let rng_idx = try_!(
-                    parse_int::parse::<u8>(&rng_idx_str),
-                    "cannot parse RNG index"
-                );
-                let enemy_init = playground::EnemyInit {
-                    sprite_idx_base,
-                    program,
-                    boss,
-                    difficulty,
-                    shot_with_rank,
-                    accel_shot_with_rank,
-                    homing_shot_with_rank,
-                    extra_act_with_rank,
-                    accel_with_rank,
-                    rank,
-                    x: x_ini,
-                    y: y_ini,
-                };
-                game.second_round = second_round;
-                game.stage = stage;
-                game.rng_idx = rng_idx;
-                break Ok(playground::EnemySpawner::new(
-                    spawn_interval,
-                    spawn_count,
-                    &entrypoints,
-                    enemy_init,
-                ));
+        }};
+    }
+
+    macro_rules! seek {
+        ($frame:expr) => {{
+            match timeline.seek($frame) {
+                Ok(inner) => {
+                    sim = inner;
+                    audio_player.skip(&sim);
+                    playback.set_paused(true);
+                    breakpoint_hit = None;
+                }
+                Err(e) => warn!("cannot seek: {}", e),
             }
         }};
     }
@@ -159,91 +296,653 @@ async fn main() -> eyre::Result<()> {
     loop {
         clear_background(GRAY);
 
+        for path in poll_dropped_files(&mut dropped_paths) {
+            open_asm!(playground::AsmDocument::import(&path));
+        }
+
+        // 外部のエディタで保存されたら読み直して再実行する。
+        // 未保存の変更がある場合は、読み直すかどうかを Control ウィンドウで選ばせる。
+        if get_time() - asm_watch_time >= ASM_WATCH_INTERVAL {
+            asm_watch_time = get_time();
+            match asm_doc.poll_external_change(&fields.assembly) {
+                Ok(Some(text)) => {
+                    info!("asm reloaded");
+                    fields.assembly = text;
+                    play!();
+                }
+                Ok(None) => {}
+                Err(e) => warn!("cannot reload asm: {}", e),
+            }
+        }
+
+        // キー操作は最後にクリックした場所に送る。UI ウィンドウ内 (入力欄など) を
+        // クリックした後は、画面をクリックし直すまでショートカットと自機操作を無効にする。
+        if is_mouse_button_pressed(MouseButton::Left) || is_mouse_button_pressed(MouseButton::Right)
+        {
+            let (mx, my) = mouse_position();
+            keys_to_screen = !root_ui().is_mouse_over(vec2(mx, my));
+        }
+
+        if keys_to_screen {
+            handle_playback_keys(&mut playback);
+            if is_key_pressed(KeyCode::Comma) && sim.frame_count() > 0 {
+                seek!(sim.frame_count() - 1);
+            }
+        }
+
+        // UI を止めないよう、1 フレームに開始 index 1 つ分ずつ進める。
+        // rng の欄を畳んでいても進むよう、ウィンドウの外で進める。
+        if let Some(sweep) = rng_sweep.as_mut() {
+            sweep.step();
+        }
+
         widgets::Window::new(hash!(), vec2(512., 0.), vec2(512., 768.))
             .label("Control")
             .titlebar(true)
-            .ui(&mut *root_ui(), |ui| {
+            .ui(&mut root_ui(), |ui| {
                 // preset
+                let variants = preset_variants
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .collect::<Vec<_>>();
                 ui.combo_box(
                     hash!(),
                     "<- presets", // XXX: ラベルが空だと項目リストが正しく表示されない?
-                    &preset_variants,
+                    &variants,
                     &mut preset_idx,
                 );
                 if ui.button(None, "Load Preset") {
                     load_preset!();
                 }
+                ui.input_text(hash!(), "<- preset name", &mut preset_name);
+                ui.input_text(hash!(), "<- preset id", &mut preset_id_str);
+                ui.checkbox(hash!(), "<- overwrite", &mut preset_overwrite);
+                if ui.button(None, "Save as Preset") {
+                    match save_user_preset(&preset_name, &preset_id_str, &fields, preset_overwrite)
+                    {
+                        Ok(()) => {
+                            preset_overwrite = false;
+                            presets = load_presets(&mut build_errors.other);
+                            preset_variants = make_preset_variants(&presets);
+                        }
+                        Err(e) => report_error!("cannot save preset: {}", e),
+                    }
+                }
+                ui.input_text(hash!(), "<- preset file", &mut preset_path);
+                if ui.button(None, "Load Preset File") {
+                    match playground::PresetFile::load(&preset_path) {
+                        Ok(preset) => {
+                            preset.apply_to_fields(&mut fields);
+                            asm_doc = playground::AsmDocument::untitled(&fields.assembly);
+                        }
+                        Err(e) => report_error!("cannot load preset file: {}", e),
+                    }
+                }
+                ui.tree_node(hash!(), "stage", |ui| {
+                    ui.input_text(hash!(), "<- stage schedule", &mut stage_path);
+                    if ui.button(None, "Play Stage") {
+                        play_stage!();
+                    }
+                });
                 ui.separator();
 
                 // enemy init
                 ui.tree_node(hash!(), "enemy params", |ui| {
-                    ui.input_text(hash!(), "<- sprite base", &mut sprite_idx_base_str);
-                    ui.checkbox(hash!(), "<- boss", &mut boss);
-                    ui.input_text(hash!(), "<- difficulty", &mut difficulty_str);
-                    ui.checkbox(hash!(), "<- do not shoot at low rank", &mut shot_with_rank);
+                    ui.input_text(hash!(), "<- sprite base", &mut fields.sprite_idx_base);
+                    field_error_label(ui, &build_errors, playground::FieldId::SpriteIdxBase);
+                    ui.checkbox(hash!(), "<- boss", &mut fields.boss);
+                    ui.input_text(hash!(), "<- difficulty", &mut fields.difficulty);
+                    field_error_label(ui, &build_errors, playground::FieldId::Difficulty);
+                    ui.checkbox(
+                        hash!(),
+                        "<- do not shoot at low rank",
+                        &mut fields.shot_with_rank,
+                    );
                     ui.checkbox(
                         hash!(),
                         "<- accel shots with rank",
-                        &mut accel_shot_with_rank,
+                        &mut fields.accel_shot_with_rank,
                     );
                     ui.checkbox(
                         hash!(),
                         "<- shoot homing bullets at high rank",
-                        &mut homing_shot_with_rank,
+                        &mut fields.homing_shot_with_rank,
                     );
                     ui.checkbox(
                         hash!(),
                         "<- extra action at high rank",
-                        &mut extra_act_with_rank,
+                        &mut fields.extra_act_with_rank,
                     );
-                    ui.checkbox(hash!(), "<- accel with rank", &mut accel_with_rank);
-                    ui.input_text(hash!(), "<- initial position x", &mut x_ini_str);
-                    ui.input_text(hash!(), "<- initial position y", &mut y_ini_str);
+                    ui.checkbox(hash!(), "<- accel with rank", &mut fields.accel_with_rank);
+                    ui.input_text(hash!(), "<- initial position x", &mut fields.x_ini);
+                    field_error_label(ui, &build_errors, playground::FieldId::XIni);
+                    ui.input_text(hash!(), "<- initial position y", &mut fields.y_ini);
+                    field_error_label(ui, &build_errors, playground::FieldId::YIni);
                 });
-                ui.editbox(hash!(), vec2(500., 500.), &mut assembly);
+                if cfg!(not(target_arch = "wasm32")) {
+                    ui.input_text(hash!(), "<- asm file", &mut asm_path);
+                    if ui.button(None, "Open") {
+                        open_asm!(playground::AsmDocument::open(&asm_path));
+                    }
+                    ui.same_line(0.);
+                    if ui.button(None, "Save") {
+                        match asm_doc.save(&fields.assembly) {
+                            Ok(()) => info!("asm saved"),
+                            Err(e) => warn!("cannot save asm: {}", e),
+                        }
+                    }
+                    ui.same_line(0.);
+                    if ui.button(None, "Save As") {
+                        match asm_doc.save_as(&asm_path, &fields.assembly) {
+                            Ok(()) => remember_recent_file(&mut recent_files, &asm_path),
+                            Err(e) => warn!("cannot save asm: {}", e),
+                        }
+                    }
+                    let mut recent_clicked = None;
+                    ui.tree_node(hash!(), "recent files", |ui| {
+                        for path in recent_files.paths() {
+                            if ui.button(None, path.display().to_string().as_str()) {
+                                recent_clicked = Some(path.clone());
+                            }
+                        }
+                    });
+                    if let Some(path) = recent_clicked {
+                        open_asm!(playground::AsmDocument::open(&path));
+                    }
+                }
+                ui.label(None, &asm_doc.title(&fields.assembly));
+                if asm_doc.external_change().is_some() {
+                    ui.label(None, "file changed on disk (you have unsaved edits):");
+                    if ui.button(None, "Reload") {
+                        fields.assembly = asm_doc.take_external_change().unwrap();
+                        info!("asm reloaded");
+                        play!();
+                    }
+                    ui.same_line(0.);
+                    if ui.button(None, "Keep") {
+                        asm_doc.take_external_change();
+                    }
+                }
+                ui.editbox(hash!(), vec2(500., 500.), &mut fields.assembly);
+                asm_error_panel(ui, &build_errors, &fields.assembly, &mut selected_asm_error);
                 ui.separator();
 
                 // spawn
                 ui.tree_node(hash!(), "spawn", |ui| {
-                    ui.input_text(hash!(), "<- spawn interval", &mut spawn_interval_str);
-                    ui.input_text(hash!(), "<- spawn count", &mut spawn_count_str);
-                    ui.input_text(hash!(), "<- entrypoints", &mut entrypoints_str);
+                    ui.input_text(hash!(), "<- spawn interval", &mut fields.spawn_interval);
+                    field_error_label(ui, &build_errors, playground::FieldId::SpawnInterval);
+                    ui.input_text(hash!(), "<- spawn count", &mut fields.spawn_count);
+                    field_error_label(ui, &build_errors, playground::FieldId::SpawnCount);
+                    ui.input_text(hash!(), "<- entrypoints", &mut fields.entrypoints);
+                    field_error_label(ui, &build_errors, playground::FieldId::Entrypoints);
                     ui.separator();
                 });
 
                 // env
-                ui.checkbox(hash!(), "<- 2nd round", &mut second_round);
-                ui.input_text(hash!(), "<- stage", &mut stage_str);
-                ui.input_text(hash!(), "<- rank", &mut rank_str);
-                ui.input_text(hash!(), "<- RNG index", &mut rng_idx_str);
+                ui.checkbox(hash!(), "<- 2nd round", &mut fields.second_round);
+                ui.input_text(hash!(), "<- stage", &mut fields.stage);
+                field_error_label(ui, &build_errors, playground::FieldId::Stage);
+                ui.input_text(hash!(), "<- rank", &mut fields.rank);
+                field_error_label(ui, &build_errors, playground::FieldId::Rank);
+                ui.checkbox(
+                    hash!(),
+                    "<- experimental rank (not the real game's rules)",
+                    &mut fields.experimental_rank,
+                );
+                ui.input_text(hash!(), "<- RNG index", &mut fields.rng_idx);
+                field_error_label(ui, &build_errors, playground::FieldId::RngIdx);
+                ui.input_text(
+                    hash!(),
+                    "<- pin rand (\"0x80\" or \"1, 2, 3\")",
+                    &mut fields.rng_pin,
+                );
+                field_error_label(ui, &build_errors, playground::FieldId::RngPin);
 
                 if ui.button(None, "Play") {
-                    match build_enemy_spawner!() {
-                        Ok(inner) => spawner = Some(inner),
-                        Err(e) => warn!("{}", e),
+                    play!();
+                }
+                ui.separator();
+
+                // playback
+                let pause_label = if playback.is_paused() {
+                    "Resume"
+                } else {
+                    "Pause"
+                };
+                if ui.button(None, pause_label) {
+                    playback.toggle_pause();
+                }
+                ui.same_line(0.);
+                if ui.button(None, "Back") && sim.frame_count() > 0 {
+                    seek!(sim.frame_count() - 1);
+                }
+                ui.same_line(0.);
+                if ui.button(None, "Step") {
+                    playback.step(1);
+                }
+                ui.input_text(hash!(), "<- step count", &mut step_count_str);
+                if ui.button(None, "Step N") {
+                    match parse_int::parse::<usize>(&step_count_str) {
+                        Ok(n) if n > playground::STEP_COUNT_MAX => {
+                            warn!("step count must be at most {}", playground::STEP_COUNT_MAX)
+                        }
+                        Ok(n) => playback.step(n),
+                        Err(e) => warn!("cannot parse step count: {}", e),
+                    }
+                }
+                for (i, &speed) in playground::PlaybackSpeed::ALL.iter().enumerate() {
+                    if i > 0 {
+                        ui.same_line(0.);
+                    }
+                    if ui.button(None, speed.label()) {
+                        playback.set_speed(speed);
+                    }
+                }
+                ui.label(
+                    None,
+                    &format!(
+                        "frame: {}  rank: {}  speed: {}{}",
+                        sim.frame_count(),
+                        sim.game().rank.value(),
+                        playback.speed().label(),
+                        if playback.is_paused() {
+                            " (paused)"
+                        } else {
+                            ""
+                        }
+                    ),
+                );
+                if !keys_to_screen {
+                    ui.label(None, "(click the screen to use keyboard shortcuts)");
+                }
+                if !timeline.is_empty() {
+                    let mut pos = sim.frame_count() as f32;
+                    ui.slider(hash!(), "<- timeline", 0. ..timeline.len() as f32, &mut pos);
+                    let frame = pos.round() as usize;
+                    if frame != sim.frame_count() {
+                        seek!(frame);
+                    }
+                }
+                ui.separator();
+
+                // save state
+                ui.input_text(hash!(), "<- save state path", &mut savestate_path);
+                if ui.button(None, "Save State") {
+                    let state = playground::SaveState::new(
+                        fields.clone(),
+                        timeline.clone(),
+                        sim.frame_count(),
+                    );
+                    match state.save(&savestate_path) {
+                        Ok(()) => info!("state saved: {}", savestate_path),
+                        Err(e) => warn!("cannot save state: {}", e),
+                    }
+                }
+                ui.same_line(0.);
+                if ui.button(None, "Load State") {
+                    match playground::SaveState::load(&savestate_path) {
+                        Ok(state) => {
+                            fields = state.fields;
+                            asm_doc = playground::AsmDocument::untitled(&fields.assembly);
+                            timeline = state.timeline;
+                            tracer.clear();
+                            selected_spawn_idx = None;
+                            source_map = build_source_map(&fields.assembly);
+                            // トラップは保存されないので、読み込んだ spawner に設定し直す。
+                            match timeline.spawners_mut() {
+                                [scheduled] => instrument_breakpoints(
+                                    &mut breakpoints,
+                                    source_map.as_ref(),
+                                    &mut scheduled.spawner,
+                                ),
+                                _ => breakpoints.clear_traps(),
+                            }
+                            seek!(state.frame);
+                        }
+                        Err(e) => warn!("cannot load state: {}", e),
+                    }
+                }
+                ui.separator();
+
+                // trace
+                if ui.button(None, "Export Trace (CSV)") {
+                    if let Err(e) = export_trace(&tracer, "trace.csv", ExportFormat::Csv) {
+                        warn!("cannot export trace: {}", e);
+                    }
+                }
+                ui.same_line(0.);
+                if ui.button(None, "Export Trace (JSON)") {
+                    if let Err(e) = export_trace(&tracer, "trace.json", ExportFormat::Json) {
+                        warn!("cannot export trace: {}", e);
                     }
-                    enemys.clear();
                 }
             });
 
-        if let Some(inner) = spawner.as_mut() {
-            if let Some(enemy) = inner.step() {
-                enemys.push(enemy);
+        let input = if keys_to_screen {
+            read_hero_input()
+        } else {
+            playground::HeroInput::default()
+        };
+        let budget = playback.tick();
+        let time_start = get_time();
+        for i in 0.. {
+            let proceed = match budget {
+                playground::StepBudget::Frames(n) => i < n,
+                playground::StepBudget::AsFastAsPossible => {
+                    get_time() - time_start < MAX_SPEED_TIME_BUDGET
+                }
+            };
+            if !proceed {
+                break;
+            }
+
+            // 巻き戻した地点から再開したら、以後の履歴は捨てる。
+            timeline.truncate(sim.frame_count());
+            tracer.truncate(sim.frame_count());
+
+            let death_count = sim.game().hero.death_count();
+            sim.step_with_input(input)?;
+            timeline.record_step(input, &sim);
+            tracer.record(&sim);
+            if sim.game().hero.death_count() > death_count {
+                info!("hero died (death count: {})", sim.game().hero.death_count());
+            }
+
+            if let Some(hit) = breakpoints.check(&sim) {
+                playback.set_paused(true);
+                selected_spawn_idx = Some(hit.spawn_idx);
+                breakpoint_hit = Some(hit);
+                break;
             }
         }
 
-        for enemy in &mut enemys {
-            enemy.step(&mut game)?;
+        audio_player.sync(&sim).await;
+
+        // 画面は 2 倍に拡大して描画している。
+        let (mx, my) = mouse_position();
+        let (cursor_x, cursor_y) = (mx / 2., my / 2.);
+        if is_mouse_button_pressed(MouseButton::Right) {
+            timeline.truncate(sim.frame_count());
+            tracer.truncate(sim.frame_count());
+            sim.damage_enemy_at(cursor_x, cursor_y);
+            timeline.record_damage(cursor_x, cursor_y);
         }
-        enemys.retain(|enemy| matches!(enemy.state(), bytecode::EnemyState::Alive));
+        if is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(enemy) = sim
+                .enemys()
+                .iter()
+                .rev()
+                .find(|enemy| enemy.contains(cursor_x, cursor_y))
+            {
+                selected_spawn_idx = Some(enemy.spawn_index());
+            }
+        }
+
+        widgets::Window::new(hash!(), vec2(0., 480.), vec2(512., 288.))
+            .label("Debugger")
+            .titlebar(true)
+            .ui(&mut root_ui(), |ui| {
+                // breakpoints
+                ui.input_text(hash!(), "<- breakpoint line", &mut breakpoint_line_str);
+                ui.input_text(
+                    hash!(),
+                    "<- condition (e.g. x < 16 && spawn == 3)",
+                    &mut breakpoint_cond_str,
+                );
+                if ui.button(None, "Add Breakpoint") {
+                    match breakpoint_parse(&breakpoint_line_str, &breakpoint_cond_str) {
+                        Ok(bp) => breakpoints.add(bp),
+                        Err(e) => warn!("cannot add breakpoint: {}", e),
+                    }
+                }
+                let mut remove_idx = None;
+                for (i, bp) in breakpoints.breakpoints().iter().enumerate() {
+                    let label = format!(
+                        "[x] line {}{}",
+                        bp.line + 1,
+                        if bp.condition.is_some() {
+                            " (cond)"
+                        } else {
+                            ""
+                        }
+                    );
+                    if ui.button(None, label.as_str()) {
+                        remove_idx = Some(i);
+                    }
+                }
+                if let Some(i) = remove_idx {
+                    breakpoints.remove(i);
+                }
+                if breakpoints.is_pending() {
+                    ui.label(None, "(press Play to apply breakpoint changes)");
+                }
+                for divergence in sim.trap_divergences() {
+                    ui.label(
+                        None,
+                        &format!(
+                            "#{}: breakpoints off from frame {} (trapped program diverged)",
+                            divergence.spawn_idx, divergence.frame
+                        ),
+                    );
+                }
+                if let (Some(hit), Some(srcmap)) = (breakpoint_hit, source_map.as_ref()) {
+                    breakpoint_source_panel(ui, srcmap, hit);
+                }
+                ui.separator();
+
+                ui.tree_node(hash!(), "sound", |ui| {
+                    ui.checkbox(hash!(), "<- mute", &mut audio_player.muted);
+                    let music = match sim.game().music {
+                        Some(id) => id.to_string(),
+                        None => "stage".to_owned(),
+                    };
+                    ui.label(None, &format!("music: {}", music));
+                    let log = sim.sound_log();
+                    for entry in &log[log.len().saturating_sub(SOUND_LOG_DISPLAY_COUNT)..] {
+                        ui.label(None, &format!("frame {}: {}", entry.frame, entry.event));
+                    }
+                });
+                ui.tree_node(hash!(), "game calls", |ui| {
+                    ui.input_text(hash!(), "<- filter (e.g. \"#3 rand\")", &mut call_filter);
+                    let filter = match playground::CallFilter::parse(&call_filter) {
+                        Ok(filter) => filter,
+                        Err(e) => {
+                            ui.label(None, &format!("  ^ {}", e));
+                            return;
+                        }
+                    };
+                    let log = sim.game_calls();
+                    // 書き出しのときだけ全件を絞り込む。
+                    for (label, path, format) in [
+                        ("Export Calls (CSV)", "calls.csv", ExportFormat::Csv),
+                        ("Export Calls (JSON)", "calls.json", ExportFormat::Json),
+                    ] {
+                        if ui.button(None, label) {
+                            let calls = playground::filter_calls(log, &filter);
+                            if let Err(e) = export_calls(&calls, path, format) {
+                                warn!("cannot export calls: {}", e);
+                            }
+                        }
+                        ui.same_line(0.);
+                    }
+                    ui.label(
+                        None,
+                        &format!("(last {} calls kept)", playground::CALL_LOG_MAX),
+                    );
+                    let mut recent: Vec<_> = log
+                        .calls()
+                        .iter()
+                        .rev()
+                        .filter(|call| filter.matches(call))
+                        .take(CALL_LOG_DISPLAY_COUNT)
+                        .collect();
+                    recent.reverse();
+                    for call in recent {
+                        ui.label(None, &call.to_string());
+                    }
+                });
+                ui.tree_node(hash!(), "rng", |ui| {
+                    let game = sim.game();
+                    ui.label(
+                        None,
+                        &format!("index: {:#04X} mode: {:?}", game.rng_idx, game.rng_mode),
+                    );
+                    rng_table_panel(ui, game.rng_idx, sim.game_calls());
+                    let mut recent: Vec<_> = sim
+                        .game_calls()
+                        .calls()
+                        .iter()
+                        .rev()
+                        .filter_map(|call| match call.kind {
+                            playground::GameCallKind::Rand { ret, .. } => Some(ret),
+                            _ => None,
+                        })
+                        .take(RNG_CONSUMED_DISPLAY_COUNT)
+                        .collect();
+                    recent.reverse();
+                    ui.label(
+                        None,
+                        &format!(
+                            "consumed {}: {}",
+                            sim.game_calls().rand_count(),
+                            itertools::join(recent.iter().map(|x| format!("{:02X}", x)), " ")
+                        ),
+                    );
+                    ui.separator();
+
+                    ui.input_text(hash!(), "<- sweep frames", &mut rng_sweep_frames_str);
+                    if ui.button(None, "Sweep All 128 Start Indices") {
+                        match parse_int::parse::<usize>(&rng_sweep_frames_str) {
+                            Ok(_) if timeline.spawners().is_empty() => {
+                                warn!("press Play before sweeping");
+                            }
+                            Ok(frames) if frames > playground::RNG_SWEEP_FRAMES_MAX => {
+                                warn!(
+                                    "sweep frames must be at most {}",
+                                    playground::RNG_SWEEP_FRAMES_MAX
+                                );
+                            }
+                            Ok(frames) => {
+                                rng_sweep = Some(playground::RngSweep::new(
+                                    timeline.game(),
+                                    timeline.spawners(),
+                                    frames,
+                                ));
+                            }
+                            Err(e) => warn!("invalid sweep frames: {}", e),
+                        }
+                    }
+                    if let Some(sweep) = rng_sweep.as_ref() {
+                        let results = sweep.results();
+                        if sweep.is_done() {
+                            ui.label(None, &playground::rng_sweep_summary(results));
+                        } else {
+                            ui.label(
+                                None,
+                                &format!(
+                                    "sweeping... {}/{}",
+                                    results.len(),
+                                    playground::RNG_TABLE_LEN
+                                ),
+                            );
+                        }
+                        for result in results {
+                            ui.label(None, &result.to_string());
+                        }
+                    }
+                });
+                ui.tree_node(hash!(), "listing", |ui| match source_map.as_ref() {
+                    Some(srcmap) => {
+                        for row in srcmap.listing() {
+                            for label in &row.labels {
+                                ui.label(None, &format!("{}:", label));
+                            }
+                            let bytes = itertools::join(
+                                row.bytes.iter().map(|b| format!("{:02X}", b)),
+                                " ",
+                            );
+                            let text = format!("{:#06X}  {:<12} {}", row.addr, bytes, row.text);
+                            // クリックでエントリポイントに追加する (ラベルがあればラベルで)。
+                            if ui.button(None, text.as_str()) {
+                                let entry = match row.labels.first() {
+                                    Some(label) => label.clone(),
+                                    None => format!("{:#04X}", row.addr),
+                                };
+                                entrypoints_push(&mut fields, &entry);
+                            }
+                        }
+                    }
+                    None => ui.label(None, "(press Play to assemble)"),
+                });
+                ui.separator();
+
+                for enemy in sim.enemys() {
+                    let line = source_map
+                        .as_ref()
+                        .and_then(|srcmap| {
+                            let i = srcmap.line_of_addr(enemy.pc()?)?;
+                            Some(format!("{:3}: {}", i + 1, srcmap.lines()[i].text.trim()))
+                        })
+                        .unwrap_or_default();
+                    let pc = match enemy.pc() {
+                        Some(pc) => format!("{:#04X}", pc),
+                        None => "?".to_owned(),
+                    };
+                    let label = format!("#{} pc={} {}", enemy.spawn_index(), pc, line);
+                    if ui.button(None, label.as_str()) {
+                        selected_spawn_idx = Some(enemy.spawn_index());
+                    }
+                }
+                ui.separator();
+
+                let selected = selected_spawn_idx
+                    .and_then(|idx| sim.enemys().iter().find(|enemy| enemy.spawn_index() == idx));
+                if let Some(enemy) = selected {
+                    ui.label(
+                        None,
+                        &format!(
+                            "#{} entrypoint={:#04X} x={} y={} sprite={:#04X}",
+                            enemy.spawn_index(),
+                            enemy.entrypoint(),
+                            enemy.x(),
+                            enemy.y(),
+                            enemy.sprite_index()
+                        ),
+                    );
+                    for line in enemy.debug_lines() {
+                        ui.label(None, &line);
+                    }
+                }
+            });
 
         set_render_target_camera(screen);
 
         clear_background(BLACK);
-        for enemy in &enemys {
+        let game = sim.game();
+        for enemy in sim.enemys() {
             let tex = playground::METASPRITES[usize::from(enemy.sprite_index())];
             draw_texture(tex, enemy.x().into(), enemy.y().into(), WHITE);
         }
+        if let Some(enemy) = selected_spawn_idx
+            .and_then(|idx| sim.enemys().iter().find(|enemy| enemy.spawn_index() == idx))
+        {
+            let color = match breakpoint_hit {
+                Some(hit) if hit.spawn_idx == enemy.spawn_index() => RED,
+                _ => GREEN,
+            };
+            draw_rectangle_lines(enemy.x().into(), enemy.y().into(), 16., 16., 1., color);
+        }
+        if game.hero.is_visible() {
+            let tex = playground::METASPRITES[usize::from(playground::HERO_SPRITE_IDX)];
+            draw_texture(tex, game.hero.x.into(), game.hero.y.into(), WHITE);
+        }
+        for shot in &game.shots {
+            draw_rectangle(shot.x() - 1., shot.y(), 2., 6., WHITE);
+        }
+        for bullet in &game.bullets {
+            let color = if bullet.is_homing() { RED } else { YELLOW };
+            draw_circle(bullet.x(), bullet.y(), 2., color);
+        }
 
         set_default_camera();
 
@@ -262,41 +961,326 @@ async fn main() -> eyre::Result<()> {
     }
 }
 
-fn make_preset_variants() -> Vec<String> {
-    playground::ENEMY_GROUP_PRESETS
-        .iter()
-        .map(|e| format!("{:#04X} {}", e.id, e.name))
-        .collect()
+/// Space: 一時停止/再開, Period: コマ送り, Minus/Equal: 減速/加速
+/// (Comma: 1 フレーム戻る は巻き戻しの都合で main 側で処理する)
+fn handle_playback_keys(playback: &mut playground::Playback) {
+    if is_key_pressed(KeyCode::Space) {
+        playback.toggle_pause();
+    }
+    if is_key_pressed(KeyCode::Period) {
+        playback.step(1);
+    }
+    if is_key_pressed(KeyCode::Minus) {
+        playback.set_speed(playback.speed().slower());
+    }
+    if is_key_pressed(KeyCode::Equal) {
+        playback.set_speed(playback.speed().faster());
+    }
 }
 
-fn entrypoints_format(entrypoints: &[usize]) -> String {
-    itertools::join(entrypoints, ", ")
+fn read_hero_input() -> playground::HeroInput {
+    playground::HeroInput {
+        left: is_key_down(KeyCode::Left),
+        right: is_key_down(KeyCode::Right),
+        up: is_key_down(KeyCode::Up),
+        down: is_key_down(KeyCode::Down),
+        shoot: is_key_down(KeyCode::Z),
+    }
 }
 
-fn entrypoints_parse(entrypoints_str: &str, count: usize) -> eyre::Result<Vec<usize>> {
-    let entrypoints = entrypoints_str
-        .split(',')
-        .map(|s| parse_int::parse::<usize>(s.trim()))
-        .collect::<Result<Vec<_>, _>>()?;
-    eyre::ensure!(entrypoints.len() == count, "entrypoint count mismatch");
+fn build_source_map(assembly: &str) -> Option<playground::SourceMap> {
+    match playground::SourceMap::build(assembly) {
+        Ok(srcmap) => Some(srcmap),
+        Err(e) => {
+            warn!("cannot build source map: {}", e);
+            None
+        }
+    }
+}
 
-    Ok(entrypoints)
+/// spawner にブレークポイントのトラップを設定する。ソースマップがなければトラップを外す。
+fn instrument_breakpoints(
+    breakpoints: &mut playground::BreakpointSet,
+    srcmap: Option<&playground::SourceMap>,
+    spawner: &mut playground::EnemySpawner,
+) {
+    match srcmap {
+        Some(srcmap) => {
+            if let Err(e) = breakpoints.instrument(srcmap, spawner) {
+                warn!("cannot set breakpoints: {}", e);
+            }
+        }
+        None => {
+            breakpoints.clear_traps();
+            spawner.clear_trap_program();
+        }
+    }
 }
 
-fn stage_parse(stage_str: &str) -> eyre::Result<u8> {
-    const RANGE: std::ops::RangeInclusive<u8> = 1..=16;
+/// エントリポイント欄の末尾に entry を追加し、spawn count をその個数に合わせる。
+fn entrypoints_push(fields: &mut playground::EditorFields, entry: &str) {
+    if fields.entrypoints.trim().is_empty() {
+        fields.entrypoints = entry.to_owned();
+    } else {
+        fields.entrypoints = format!("{}, {}", fields.entrypoints.trim_end(), entry);
+    }
+    fields.spawn_count = fields.entrypoints.split(',').count().to_string();
+}
 
-    let stage = parse_int::parse::<u8>(stage_str)?;
-    eyre::ensure!(RANGE.contains(&stage), "stage must be within {:?}", RANGE);
+/// 行番号は 1-based で入力する。条件は空でもよい。
+fn breakpoint_parse(line_str: &str, cond_str: &str) -> eyre::Result<playground::Breakpoint> {
+    let line = parse_int::parse::<usize>(line_str.trim())?;
+    eyre::ensure!(line >= 1, "line number must be >= 1");
 
-    Ok(stage)
+    let condition = if cond_str.trim().is_empty() {
+        None
+    } else {
+        Some(playground::Condition::parse(cond_str)?)
+    };
+
+    Ok(playground::Breakpoint {
+        line: line - 1,
+        condition,
+    })
+}
+
+enum ExportFormat {
+    Csv,
+    Json,
 }
 
-fn rank_parse(rank_str: &str) -> eyre::Result<u8> {
-    const RANGE: std::ops::RangeInclusive<u8> = 0..=7;
+fn export_trace(
+    tracer: &playground::TraceRecorder,
+    path: &str,
+    format: ExportFormat,
+) -> eyre::Result<()> {
+    let wtr = std::io::BufWriter::new(std::fs::File::create(path)?);
+    match format {
+        ExportFormat::Csv => tracer.write_csv(wtr)?,
+        ExportFormat::Json => tracer.write_json(wtr)?,
+    }
+    info!("trace exported: {}", path);
 
-    let rank = parse_int::parse::<u8>(rank_str)?;
-    eyre::ensure!(RANGE.contains(&rank), "rank must be within {:?}", RANGE);
+    Ok(())
+}
+
+fn export_calls(
+    calls: &[&playground::GameCall],
+    path: &str,
+    format: ExportFormat,
+) -> eyre::Result<()> {
+    let wtr = std::io::BufWriter::new(std::fs::File::create(path)?);
+    match format {
+        ExportFormat::Csv => playground::write_calls_csv(calls, wtr)?,
+        ExportFormat::Json => playground::write_calls_json(calls, wtr)?,
+    }
+    info!("calls exported: {}", path);
+
+    Ok(())
+}
+
+// RNG テーブルを 16 個ずつ表示する。現在の index を [] で、消費済みの index を * で示す。
+fn rng_table_panel(ui: &mut macroquad::ui::Ui, rng_idx: u8, log: &playground::CallLog) {
+    let current = usize::from(rng_idx) % playground::RNG_TABLE_LEN;
+
+    for (row, values) in playground::RNG_TABLE.chunks(16).enumerate() {
+        let cells = values.iter().enumerate().map(|(col, value)| {
+            let idx = 16 * row + col;
+            if idx == current {
+                format!("[{:02X}]", value)
+            } else if log.is_rng_consumed(idx) {
+                format!("*{:02X} ", value)
+            } else {
+                format!(" {:02X} ", value)
+            }
+        });
+        ui.label(
+            None,
+            &format!("{:02X}:{}", 16 * row, itertools::join(cells, "")),
+        );
+    }
+}
+
+fn field_error_label(
+    ui: &mut macroquad::ui::Ui,
+    errors: &playground::BuildErrors,
+    field: playground::FieldId,
+) {
+    if let Some(msg) = errors.field(field) {
+        ui.label(None, &format!("  ^ {}", msg));
+    }
+}
+
+/// アセンブルエラーの一覧。クリックしたエラーの行の前後を、エラーの行に印を付けて表示する。
+///
+/// macroquad 0.3 の editbox はカーソル位置やスタイルを外から操作できないので、
+/// editbox 自体ではなくこのパネルの中で該当行を示す。
+fn asm_error_panel(
+    ui: &mut macroquad::ui::Ui,
+    errors: &playground::BuildErrors,
+    assembly: &str,
+    selected: &mut Option<usize>,
+) {
+    const CONTEXT_LINES: usize = 2;
+
+    for message in &errors.other {
+        ui.label(None, message);
+    }
+    if errors.asm.is_empty() {
+        return;
+    }
+
+    ui.label(None, &format!("{} assemble error(s):", errors.asm.len()));
+    for (i, e) in errors.asm.iter().enumerate() {
+        let text = format!("{}: {}", e.line + 1, e.message);
+        if ui.button(None, text.as_str()) {
+            *selected = Some(i);
+        }
+    }
+
+    let e = match selected.and_then(|i| errors.asm.get(i)) {
+        Some(e) => e,
+        None => return,
+    };
+    let lines: Vec<&str> = assembly.lines().collect();
+    let first = e.line.saturating_sub(CONTEXT_LINES);
+    let last = (e.line + CONTEXT_LINES).min(lines.len().saturating_sub(1));
+    for (i, text) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let mark = if i == e.line { ">" } else { "|" };
+        ui.label(None, &format!("{:4} {} {}", i + 1, mark, text));
+    }
+}
 
-    Ok(rank)
+/// ブレークポイントで停止した行の前後を、停止した行を赤く強調して表示する。
+///
+/// `asm_error_panel` と同じく、editbox の外に表示する。
+fn breakpoint_source_panel(
+    ui: &mut macroquad::ui::Ui,
+    srcmap: &playground::SourceMap,
+    hit: playground::BreakpointHit,
+) {
+    const CONTEXT_LINES: usize = 2;
+
+    ui.label(
+        None,
+        &format!(">>> break: #{} at line {}", hit.spawn_idx, hit.line + 1),
+    );
+
+    let lines = srcmap.lines();
+    let first = hit.line.saturating_sub(CONTEXT_LINES);
+    let last = (hit.line + CONTEXT_LINES).min(lines.len().saturating_sub(1));
+    for (i, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let text = format!(
+            "{:4} {} {}",
+            i + 1,
+            if i == hit.line { ">" } else { "|" },
+            line.text
+        );
+        if i == hit.line {
+            let skin = {
+                let label_style = ui.style_builder().text_color(RED).build();
+                macroquad::ui::Skin {
+                    label_style,
+                    ..ui.default_skin()
+                }
+            };
+            ui.push_skin(&skin);
+            ui.label(None, &text);
+            ui.pop_skin();
+        } else {
+            ui.label(None, &text);
+        }
+    }
+}
+
+fn remember_recent_file<P: AsRef<std::path::Path>>(
+    recent_files: &mut playground::RecentFiles,
+    path: P,
+) {
+    recent_files.push(path);
+    if let Err(e) = recent_files.save(RECENT_FILES_PATH) {
+        warn!("cannot save recent files: {}", e);
+    }
+}
+
+/// ウィンドウにドロップされたファイルのうち、前回から変わったものを返す。
+///
+/// macroquad 0.3 はドロップイベントを転送しないので、miniquad が保持している
+/// 最後にドロップされたファイル一覧をポーリングする (同じファイルを続けて
+/// ドロップした場合は検出できない)。
+fn poll_dropped_files(prev: &mut Vec<std::path::PathBuf>) -> Vec<std::path::PathBuf> {
+    let ctx = unsafe { get_internal_gl() }.quad_context;
+    let paths: Vec<_> = (0..ctx.dropped_file_count())
+        .filter_map(|i| ctx.dropped_file_path(i))
+        .collect();
+
+    if paths == *prev {
+        return vec![];
+    }
+    *prev = paths.clone();
+
+    paths
+}
+
+/// 組み込み preset の後にユーザー preset を並べる。読み込めなかったものは errors に追加する。
+fn load_presets(errors: &mut Vec<String>) -> Vec<playground::PresetFile> {
+    let mut presets: Vec<_> = playground::ENEMY_GROUP_PRESETS
+        .iter()
+        .map(playground::PresetFile::from_builtin)
+        .collect();
+
+    let dir = playground::user_preset_dir();
+    for (path, preset) in playground::load_user_presets(&dir) {
+        match preset {
+            Ok(preset) => presets.push(preset),
+            Err(e) => {
+                let message = format!("cannot load preset {}: {}", path.display(), e);
+                warn!("{}", message);
+                errors.push(message);
+            }
+        }
+    }
+
+    presets
+}
+
+/// 同名のファイルがある場合、overwrite でなければ保存しない。
+fn save_user_preset(
+    name: &str,
+    id_str: &str,
+    fields: &playground::EditorFields,
+    overwrite: bool,
+) -> eyre::Result<()> {
+    let stem = playground::preset_file_stem(name)?;
+    let id = parse_int::parse::<u8>(id_str.trim())?;
+    let preset = playground::PresetFile::from_fields(id, name.trim(), fields)?;
+
+    let dir = playground::user_preset_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.toml", stem));
+    eyre::ensure!(
+        overwrite || !path.exists(),
+        "{} already exists (check \"overwrite\" to replace it)",
+        path.display()
+    );
+    preset.save(&path)?;
+    info!("preset saved: {}", path.display());
+
+    Ok(())
+}
+
+fn make_preset_variants(presets: &[playground::PresetFile]) -> Vec<String> {
+    let builtin_count = playground::ENEMY_GROUP_PRESETS.len();
+
+    presets
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            if i < builtin_count {
+                format!("{:#04X} {}", e.id, e.name)
+            } else {
+                format!("(user) {}", e.name)
+            }
+        })
+        .collect()
 }
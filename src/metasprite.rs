@@ -0,0 +1,201 @@
+#[cfg(feature = "gui")]
+use macroquad::texture::Texture2D;
+use once_cell::sync::Lazy;
+
+macro_rules! embed_bytes {
+    ($path:expr) => {{
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path))
+    }};
+}
+
+/// RGBA8 のピクセルデータ。
+#[derive(Debug)]
+pub struct MetaspriteImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl MetaspriteImage {
+    fn decode(png: &[u8]) -> eyre::Result<Self> {
+        let decoder = png::Decoder::new(png);
+        let (info, mut reader) = decoder.read_info()?;
+        eyre::ensure!(
+            info.color_type == png::ColorType::RGBA && info.bit_depth == png::BitDepth::Eight,
+            "metasprite must be RGBA8"
+        );
+
+        let mut pixels = vec![0; info.buffer_size()];
+        reader.next_frame(&mut pixels)?;
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+}
+
+pub static METASPRITE_IMAGES: Lazy<Vec<MetaspriteImage>> = Lazy::new(|| {
+    METASPRITE_PNGS
+        .iter()
+        .map(|png| MetaspriteImage::decode(png).expect("embedded metasprite must be valid"))
+        .collect()
+});
+
+#[cfg(feature = "gui")]
+pub static METASPRITES: Lazy<Vec<Texture2D>> = Lazy::new(|| {
+    METASPRITE_PNGS
+        .iter()
+        .map(|png| Texture2D::from_file_with_format(png, None))
+        .collect()
+});
+
+pub static METASPRITE_PNGS: [&[u8]; 0x90] = [
+    // {{{
+    embed_bytes!("asset/metasprite-000.png"),
+    embed_bytes!("asset/metasprite-001.png"),
+    embed_bytes!("asset/metasprite-002.png"),
+    embed_bytes!("asset/metasprite-003.png"),
+    embed_bytes!("asset/metasprite-004.png"),
+    embed_bytes!("asset/metasprite-005.png"),
+    embed_bytes!("asset/metasprite-006.png"),
+    embed_bytes!("asset/metasprite-007.png"),
+    embed_bytes!("asset/metasprite-008.png"),
+    embed_bytes!("asset/metasprite-009.png"),
+    embed_bytes!("asset/metasprite-010.png"),
+    embed_bytes!("asset/metasprite-011.png"),
+    embed_bytes!("asset/metasprite-012.png"),
+    embed_bytes!("asset/metasprite-013.png"),
+    embed_bytes!("asset/metasprite-014.png"),
+    embed_bytes!("asset/metasprite-015.png"),
+    embed_bytes!("asset/metasprite-016.png"),
+    embed_bytes!("asset/metasprite-017.png"),
+    embed_bytes!("asset/metasprite-018.png"),
+    embed_bytes!("asset/metasprite-019.png"),
+    embed_bytes!("asset/metasprite-020.png"),
+    embed_bytes!("asset/metasprite-021.png"),
+    embed_bytes!("asset/metasprite-022.png"),
+    embed_bytes!("asset/metasprite-023.png"),
+    embed_bytes!("asset/metasprite-024.png"),
+    embed_bytes!("asset/metasprite-025.png"),
+    embed_bytes!("asset/metasprite-026.png"),
+    embed_bytes!("asset/metasprite-027.png"),
+    embed_bytes!("asset/metasprite-028.png"),
+    embed_bytes!("asset/metasprite-029.png"),
+    embed_bytes!("asset/metasprite-030.png"),
+    embed_bytes!("asset/metasprite-031.png"),
+    embed_bytes!("asset/metasprite-032.png"),
+    embed_bytes!("asset/metasprite-033.png"),
+    embed_bytes!("asset/metasprite-034.png"),
+    embed_bytes!("asset/metasprite-035.png"),
+    embed_bytes!("asset/metasprite-036.png"),
+    embed_bytes!("asset/metasprite-037.png"),
+    embed_bytes!("asset/metasprite-038.png"),
+    embed_bytes!("asset/metasprite-039.png"),
+    embed_bytes!("asset/metasprite-040.png"),
+    embed_bytes!("asset/metasprite-041.png"),
+    embed_bytes!("asset/metasprite-042.png"),
+    embed_bytes!("asset/metasprite-043.png"),
+    embed_bytes!("asset/metasprite-044.png"),
+    embed_bytes!("asset/metasprite-045.png"),
+    embed_bytes!("asset/metasprite-046.png"),
+    embed_bytes!("asset/metasprite-047.png"),
+    embed_bytes!("asset/metasprite-048.png"),
+    embed_bytes!("asset/metasprite-049.png"),
+    embed_bytes!("asset/metasprite-050.png"),
+    embed_bytes!("asset/metasprite-051.png"),
+    embed_bytes!("asset/metasprite-052.png"),
+    embed_bytes!("asset/metasprite-053.png"),
+    embed_bytes!("asset/metasprite-054.png"),
+    embed_bytes!("asset/metasprite-055.png"),
+    embed_bytes!("asset/metasprite-056.png"),
+    embed_bytes!("asset/metasprite-057.png"),
+    embed_bytes!("asset/metasprite-058.png"),
+    embed_bytes!("asset/metasprite-059.png"),
+    embed_bytes!("asset/metasprite-060.png"),
+    embed_bytes!("asset/metasprite-061.png"),
+    embed_bytes!("asset/metasprite-062.png"),
+    embed_bytes!("asset/metasprite-063.png"),
+    embed_bytes!("asset/metasprite-064.png"),
+    embed_bytes!("asset/metasprite-065.png"),
+    embed_bytes!("asset/metasprite-066.png"),
+    embed_bytes!("asset/metasprite-067.png"),
+    embed_bytes!("asset/metasprite-068.png"),
+    embed_bytes!("asset/metasprite-069.png"),
+    embed_bytes!("asset/metasprite-070.png"),
+    embed_bytes!("asset/metasprite-071.png"),
+    embed_bytes!("asset/metasprite-072.png"),
+    embed_bytes!("asset/metasprite-073.png"),
+    embed_bytes!("asset/metasprite-074.png"),
+    embed_bytes!("asset/metasprite-075.png"),
+    embed_bytes!("asset/metasprite-076.png"),
+    embed_bytes!("asset/metasprite-077.png"),
+    embed_bytes!("asset/metasprite-078.png"),
+    embed_bytes!("asset/metasprite-079.png"),
+    embed_bytes!("asset/metasprite-080.png"),
+    embed_bytes!("asset/metasprite-081.png"),
+    embed_bytes!("asset/metasprite-082.png"),
+    embed_bytes!("asset/metasprite-083.png"),
+    embed_bytes!("asset/metasprite-084.png"),
+    embed_bytes!("asset/metasprite-085.png"),
+    embed_bytes!("asset/metasprite-086.png"),
+    embed_bytes!("asset/metasprite-087.png"),
+    embed_bytes!("asset/metasprite-088.png"),
+    embed_bytes!("asset/metasprite-089.png"),
+    embed_bytes!("asset/metasprite-090.png"),
+    embed_bytes!("asset/metasprite-091.png"),
+    embed_bytes!("asset/metasprite-092.png"),
+    embed_bytes!("asset/metasprite-093.png"),
+    embed_bytes!("asset/metasprite-094.png"),
+    embed_bytes!("asset/metasprite-095.png"),
+    embed_bytes!("asset/metasprite-096.png"),
+    embed_bytes!("asset/metasprite-097.png"),
+    embed_bytes!("asset/metasprite-098.png"),
+    embed_bytes!("asset/metasprite-099.png"),
+    embed_bytes!("asset/metasprite-100.png"),
+    embed_bytes!("asset/metasprite-101.png"),
+    embed_bytes!("asset/metasprite-102.png"),
+    embed_bytes!("asset/metasprite-103.png"),
+    embed_bytes!("asset/metasprite-104.png"),
+    embed_bytes!("asset/metasprite-105.png"),
+    embed_bytes!("asset/metasprite-106.png"),
+    embed_bytes!("asset/metasprite-107.png"),
+    embed_bytes!("asset/metasprite-108.png"),
+    embed_bytes!("asset/metasprite-109.png"),
+    embed_bytes!("asset/metasprite-110.png"),
+    embed_bytes!("asset/metasprite-111.png"),
+    embed_bytes!("asset/metasprite-112.png"),
+    embed_bytes!("asset/metasprite-113.png"),
+    embed_bytes!("asset/metasprite-114.png"),
+    embed_bytes!("asset/metasprite-115.png"),
+    embed_bytes!("asset/metasprite-116.png"),
+    embed_bytes!("asset/metasprite-117.png"),
+    embed_bytes!("asset/metasprite-118.png"),
+    embed_bytes!("asset/metasprite-119.png"),
+    embed_bytes!("asset/metasprite-120.png"),
+    embed_bytes!("asset/metasprite-121.png"),
+    embed_bytes!("asset/metasprite-122.png"),
+    embed_bytes!("asset/metasprite-123.png"),
+    embed_bytes!("asset/metasprite-124.png"),
+    embed_bytes!("asset/metasprite-125.png"),
+    embed_bytes!("asset/metasprite-126.png"),
+    embed_bytes!("asset/metasprite-127.png"),
+    embed_bytes!("asset/metasprite-128.png"),
+    embed_bytes!("asset/metasprite-129.png"),
+    embed_bytes!("asset/metasprite-130.png"),
+    embed_bytes!("asset/metasprite-131.png"),
+    embed_bytes!("asset/metasprite-132.png"),
+    embed_bytes!("asset/metasprite-133.png"),
+    embed_bytes!("asset/metasprite-134.png"),
+    embed_bytes!("asset/metasprite-135.png"),
+    embed_bytes!("asset/metasprite-136.png"),
+    embed_bytes!("asset/metasprite-137.png"),
+    embed_bytes!("asset/metasprite-138.png"),
+    embed_bytes!("asset/metasprite-139.png"),
+    embed_bytes!("asset/metasprite-140.png"),
+    embed_bytes!("asset/metasprite-141.png"),
+    embed_bytes!("asset/metasprite-142.png"),
+    embed_bytes!("asset/metasprite-143.png"),
+    // }}}
+];
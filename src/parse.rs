@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rng::{RngMode, RngSequence};
+use crate::srcmap::SourceMap;
+
+/// エントリポイント。バイトオフセットか、アセンブリ中のラベル名で指定する。
+///
+/// ラベルで指定しておけば、スクリプトの前の方に命令を挿入してもずれない。
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Entrypoint {
+    Offset(usize),
+    Label(String),
+}
+
+impl From<usize> for Entrypoint {
+    fn from(offset: usize) -> Self {
+        Self::Offset(offset)
+    }
+}
+
+impl From<&str> for Entrypoint {
+    fn from(label: &str) -> Self {
+        Self::Label(label.to_owned())
+    }
+}
+
+impl std::fmt::Display for Entrypoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Offset(offset) => write!(f, "{}", offset),
+            Self::Label(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+pub fn entrypoints_format(entrypoints: &[Entrypoint]) -> String {
+    itertools::join(entrypoints, ", ")
+}
+
+/// "L08, L0C, 0x1D" のような文字列をパースする。ラベルはまだ解決しない。
+pub fn entrypoints_parse(entrypoints_str: &str, count: usize) -> eyre::Result<Vec<Entrypoint>> {
+    let entrypoints = entrypoints_str
+        .split(',')
+        .map(|s| entrypoint_parse(s.trim()))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    eyre::ensure!(entrypoints.len() == count, "entrypoint count mismatch");
+
+    Ok(entrypoints)
+}
+
+fn entrypoint_parse(s: &str) -> eyre::Result<Entrypoint> {
+    if let Ok(offset) = parse_int::parse::<usize>(s) {
+        return Ok(Entrypoint::Offset(offset));
+    }
+
+    let mut chars = s.chars();
+    let head_ok = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_');
+    eyre::ensure!(
+        head_ok && chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        "invalid entrypoint: {:?}",
+        s
+    );
+
+    Ok(Entrypoint::Label(s.to_owned()))
+}
+
+/// ラベルをアセンブリ中のオフセットに解決する。
+///
+/// ラベルの解決には `SourceMap` を作る必要があるので、全てオフセットなら作らない。
+pub fn entrypoints_resolve(entrypoints: &[Entrypoint], assembly: &str) -> eyre::Result<Vec<usize>> {
+    let has_label = entrypoints
+        .iter()
+        .any(|entrypoint| matches!(entrypoint, Entrypoint::Label(_)));
+    let srcmap = if has_label {
+        Some(SourceMap::build(assembly)?)
+    } else {
+        None
+    };
+
+    entrypoints
+        .iter()
+        .map(|entrypoint| match entrypoint {
+            Entrypoint::Offset(offset) => Ok(*offset),
+            Entrypoint::Label(label) => srcmap
+                .as_ref()
+                .and_then(|srcmap| srcmap.label_addr(label))
+                .ok_or_else(|| eyre::eyre!("label not found: {}", label)),
+        })
+        .collect()
+}
+
+pub fn stage_parse(stage_str: &str) -> eyre::Result<u8> {
+    const RANGE: std::ops::RangeInclusive<u8> = 1..=16;
+
+    let stage = parse_int::parse::<u8>(stage_str)?;
+    eyre::ensure!(RANGE.contains(&stage), "stage must be within {:?}", RANGE);
+
+    Ok(stage)
+}
+
+pub fn rank_parse(rank_str: &str) -> eyre::Result<u8> {
+    const RANGE: std::ops::RangeInclusive<u8> = 0..=7;
+
+    let rank = parse_int::parse::<u8>(rank_str)?;
+    eyre::ensure!(RANGE.contains(&rank), "rank must be within {:?}", RANGE);
+
+    Ok(rank)
+}
+
+/// rand の固定値をパースする。空なら固定しない (テーブル)、1 つなら定数、
+/// "0x10, 0x80, 0xF0" のように複数なら列とする。
+pub fn rng_mode_parse(rng_str: &str) -> eyre::Result<RngMode> {
+    let rng_str = rng_str.trim();
+    if rng_str.is_empty() {
+        return Ok(RngMode::Table);
+    }
+
+    let mut values = rng_str
+        .split(',')
+        .map(|s| parse_int::parse::<u8>(s.trim()).map_err(eyre::Report::from))
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    Ok(if values.len() == 1 {
+        RngMode::Constant(values.pop().unwrap())
+    } else {
+        RngMode::Sequence(RngSequence::new(values)?)
+    })
+}
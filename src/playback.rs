@@ -0,0 +1,143 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlaybackSpeed {
+    Quarter,
+    Half,
+    Normal,
+    Double,
+    Quadruple,
+    Max,
+}
+
+impl PlaybackSpeed {
+    pub const ALL: [Self; 6] = [
+        Self::Quarter,
+        Self::Half,
+        Self::Normal,
+        Self::Double,
+        Self::Quadruple,
+        Self::Max,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Quarter => "1/4x",
+            Self::Half => "1/2x",
+            Self::Normal => "1x",
+            Self::Double => "2x",
+            Self::Quadruple => "4x",
+            Self::Max => "max",
+        }
+    }
+
+    /// 表示 1 フレームあたりに進めるフレーム数。`Max` の場合は None。
+    fn rate(self) -> Option<f32> {
+        match self {
+            Self::Quarter => Some(0.25),
+            Self::Half => Some(0.5),
+            Self::Normal => Some(1.),
+            Self::Double => Some(2.),
+            Self::Quadruple => Some(4.),
+            Self::Max => None,
+        }
+    }
+
+    pub fn slower(self) -> Self {
+        let i = Self::ALL.iter().position(|&e| e == self).unwrap();
+        Self::ALL[i.saturating_sub(1)]
+    }
+
+    pub fn faster(self) -> Self {
+        let i = Self::ALL.iter().position(|&e| e == self).unwrap();
+        Self::ALL[(i + 1).min(Self::ALL.len() - 1)]
+    }
+}
+
+/// `Playback::step` で一度に指定できる最大フレーム数。
+pub const STEP_COUNT_MAX: usize = 60 * 60 * 10;
+
+// 一時停止中のコマ送りで、表示 1 フレームの間に進める最大フレーム数。
+// 大きなコマ送りで UI が固まらないよう、残りは次の表示フレーム以降に回す。
+const PENDING_STEPS_PER_TICK: usize = 600;
+
+/// 表示 1 フレームの間にシミュレーションを何フレーム進めるか。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StepBudget {
+    Frames(usize),
+    /// 時間の許す限り進める。
+    AsFastAsPossible,
+}
+
+/// 一時停止、コマ送り、再生速度を管理する。
+#[derive(Debug)]
+pub struct Playback {
+    paused: bool,
+    speed: PlaybackSpeed,
+
+    // 1 未満の速度のための端数。
+    acc: f32,
+    // 一時停止中のコマ送りで残っているフレーム数。
+    pending_steps: usize,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: PlaybackSpeed::Normal,
+
+            acc: 0.,
+            pending_steps: 0,
+        }
+    }
+}
+
+impl Playback {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.pending_steps = 0;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    pub fn speed(&self) -> PlaybackSpeed {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: PlaybackSpeed) {
+        self.speed = speed;
+        self.acc = 0.;
+    }
+
+    /// n フレームだけ進めて一時停止する。n は `STEP_COUNT_MAX` 以下であること。
+    pub fn step(&mut self, n: usize) {
+        assert!(n <= STEP_COUNT_MAX);
+
+        self.paused = true;
+        self.pending_steps = (self.pending_steps + n).min(STEP_COUNT_MAX);
+    }
+
+    /// 表示フレームごとに 1 回呼ぶ。
+    pub fn tick(&mut self) -> StepBudget {
+        if self.paused {
+            let n = self.pending_steps.min(PENDING_STEPS_PER_TICK);
+            self.pending_steps -= n;
+            return StepBudget::Frames(n);
+        }
+
+        match self.speed.rate() {
+            Some(rate) => {
+                self.acc += rate;
+                let n = self.acc.floor();
+                self.acc -= n;
+                StepBudget::Frames(n as usize)
+            }
+            None => StepBudget::AsFastAsPossible,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::asset::EnemyGroupPreset;
+use crate::parse::{entrypoints_format, entrypoints_parse, Entrypoint};
+use crate::savestate::EditorFields;
+
+/// ユーザー preset を置くディレクトリを指定する環境変数。
+pub const USER_PRESET_DIR_ENV: &str = "SSBC_PRESET_DIR";
+
+/// TOML で保存される preset。フィールドは `EnemyGroupPreset` と同じ。
+///
+/// アセンブリは `assembly` でインラインに書くか、`assembly_file` で
+/// (preset ファイルからの相対パスで) 参照する。
+///
+/// `id` は必須。組み込み preset と同じ id なら、id で引くときはユーザー preset が優先される。
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PresetFile {
+    pub id: u8,
+    pub name: String,
+    pub spawn_interval: usize,
+    pub spawn_count: usize,
+    pub sprite_idx_base: u8,
+    pub boss: bool,
+    pub difficulty: u8,
+    pub shot_with_rank: bool,
+    pub accel_shot_with_rank: bool,
+    pub homing_shot_with_rank: bool,
+    pub extra_act_with_rank: bool,
+    pub accel_with_rank: bool,
+    pub x_ini: u8,
+    pub y_ini: u8,
+    /// オフセットかラベル名。
+    pub entrypoints: Vec<Entrypoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assembly_file: Option<PathBuf>,
+    #[serde(default)]
+    pub assembly: String,
+}
+
+impl PresetFile {
+    pub fn from_builtin(preset: &EnemyGroupPreset) -> Self {
+        Self {
+            id: preset.id,
+            name: preset.name.to_owned(),
+            spawn_interval: preset.spawn_interval,
+            spawn_count: preset.spawn_count,
+            sprite_idx_base: preset.sprite_idx_base,
+            boss: preset.boss,
+            difficulty: preset.difficulty,
+            shot_with_rank: preset.shot_with_rank,
+            accel_shot_with_rank: preset.accel_shot_with_rank,
+            homing_shot_with_rank: preset.homing_shot_with_rank,
+            extra_act_with_rank: preset.extra_act_with_rank,
+            accel_with_rank: preset.accel_with_rank,
+            x_ini: preset.x_ini,
+            y_ini: preset.y_ini,
+            entrypoints: preset.entrypoints.clone(),
+            assembly_file: None,
+            assembly: preset.assembly.to_owned(),
+        }
+    }
+
+    /// GUI の入力欄から作る。env 関連の欄は使わない。
+    pub fn from_fields(id: u8, name: &str, fields: &EditorFields) -> eyre::Result<Self> {
+        let spawn_count = parse_int::parse::<usize>(&fields.spawn_count)?;
+
+        Ok(Self {
+            id,
+            name: name.to_owned(),
+            spawn_interval: parse_int::parse(&fields.spawn_interval)?,
+            spawn_count,
+            sprite_idx_base: parse_int::parse(&fields.sprite_idx_base)?,
+            boss: fields.boss,
+            difficulty: parse_int::parse(&fields.difficulty)?,
+            shot_with_rank: fields.shot_with_rank,
+            accel_shot_with_rank: fields.accel_shot_with_rank,
+            homing_shot_with_rank: fields.homing_shot_with_rank,
+            extra_act_with_rank: fields.extra_act_with_rank,
+            accel_with_rank: fields.accel_with_rank,
+            x_ini: parse_int::parse(&fields.x_ini)?,
+            y_ini: parse_int::parse(&fields.y_ini)?,
+            entrypoints: entrypoints_parse(&fields.entrypoints, spawn_count)?,
+            assembly_file: None,
+            assembly: fields.assembly.clone(),
+        })
+    }
+
+    /// GUI の入力欄に反映する。env 関連の欄はそのまま。
+    pub fn apply_to_fields(&self, fields: &mut EditorFields) {
+        fields.sprite_idx_base = format!("{:#04X}", self.sprite_idx_base);
+        fields.boss = self.boss;
+        fields.difficulty = self.difficulty.to_string();
+        fields.shot_with_rank = self.shot_with_rank;
+        fields.accel_shot_with_rank = self.accel_shot_with_rank;
+        fields.homing_shot_with_rank = self.homing_shot_with_rank;
+        fields.extra_act_with_rank = self.extra_act_with_rank;
+        fields.accel_with_rank = self.accel_with_rank;
+        fields.x_ini = self.x_ini.to_string();
+        fields.y_ini = self.y_ini.to_string();
+        fields.assembly = self.assembly.clone();
+        fields.spawn_interval = self.spawn_interval.to_string();
+        fields.spawn_count = self.spawn_count.to_string();
+        fields.entrypoints = entrypoints_format(&self.entrypoints);
+    }
+
+    /// 読み込み時、`assembly_file` が指定されていればその内容を `assembly` に読み込む。
+    pub fn load<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        let path = path.as_ref();
+
+        let mut preset: Self = toml::from_str(&std::fs::read_to_string(path)?)?;
+        if let Some(assembly_file) = &preset.assembly_file {
+            let dir = path.parent().unwrap_or_else(|| Path::new("."));
+            preset.assembly = std::fs::read_to_string(dir.join(assembly_file))?;
+        }
+        eyre::ensure!(
+            preset.entrypoints.len() == preset.spawn_count,
+            "entrypoint count mismatch"
+        );
+
+        Ok(preset)
+    }
+
+    /// アセンブリは常にインラインで保存する。
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> eyre::Result<()> {
+        let preset = Self {
+            assembly_file: None,
+            ..self.clone()
+        };
+        std::fs::write(path, toml::to_string(&preset)?)?;
+
+        Ok(())
+    }
+}
+
+pub fn user_preset_dir() -> PathBuf {
+    std::env::var_os(USER_PRESET_DIR_ENV).map_or_else(|| PathBuf::from("presets"), PathBuf::from)
+}
+
+/// preset 名からユーザー preset のファイル名 (拡張子なし) を作る。
+///
+/// 英数字と `-` 以外は `_` に置き換えるので、パス区切りや `..` は含まれない。
+pub fn preset_file_stem(name: &str) -> eyre::Result<String> {
+    let name = name.trim();
+    eyre::ensure!(!name.is_empty(), "preset name is empty");
+
+    Ok(name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect())
+}
+
+/// ディレクトリ内の *.toml を全て読み込む。ディレクトリがなければ空を返す。
+///
+/// 先に読み込んだものと id が重複する preset はエラーとする。
+pub fn load_user_presets<P: AsRef<Path>>(dir: P) -> Vec<(PathBuf, eyre::Result<PresetFile>)> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut id_paths: Vec<(u8, PathBuf)> = vec![];
+    paths
+        .into_iter()
+        .map(|path| {
+            let preset = PresetFile::load(&path).and_then(|preset| {
+                if let Some((_, other)) = id_paths.iter().find(|(id, _)| *id == preset.id) {
+                    eyre::bail!(
+                        "duplicate preset id {:#04X} (also used by {})",
+                        preset.id,
+                        other.display()
+                    );
+                }
+                id_paths.push((preset.id, path.clone()));
+                Ok(preset)
+            });
+            (path, preset)
+        })
+        .collect()
+}
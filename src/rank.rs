@@ -0,0 +1,110 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+pub const RANK_MAX: u8 = 7;
+
+// 以下の 3 つは実機から調べた値ではなく、experimental モードのために決めた仮の値。
+
+/// この数だけ敵を倒すとランクが 1 上がる。
+const KILLS_PER_RANK_UP: usize = 32;
+
+/// この時間 (フレーム数) が経過するとランクが 1 上がる。
+const FRAMES_PER_RANK_UP: usize = 60 * 60;
+
+/// 自機がやられたときに下がるランク。
+const RANK_DOWN_ON_DEATH: u8 = 2;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RankMode {
+    /// 開始時のランクのまま変化しない。
+    #[default]
+    Fixed,
+    /// 撃破数、被弾、経過時間によって変化する、実機とは無関係の試験的なモデル。
+    #[serde(alias = "Dynamic")]
+    Experimental,
+}
+
+/// ランク (0..=7)。新たに出現する敵はその時点のランクで初期化される。
+///
+/// experimental モードの増減規則 (一定数の撃破、または一定時間の経過で 1 上がり、
+/// 自機がやられると下がる) は独自に考えたモデルで、実機のランク処理は再現していない。
+/// 実機のランクの変化を調べる用途には使えない。
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Rank {
+    pub mode: RankMode,
+    #[serde(deserialize_with = "deserialize_rank_value")]
+    value: u8,
+
+    // 前回ランクが上がってからの撃破数/経過フレーム数。
+    kill_count: usize,
+    frame_count: usize,
+}
+
+impl Rank {
+    pub fn new(mode: RankMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// 初期ランクを設定し、カウンタをクリアする。`RANK_MAX` を超える値は `RANK_MAX` にする。
+    pub fn reset(&mut self, value: u8) {
+        self.value = value.min(RANK_MAX);
+        self.kill_count = 0;
+        self.frame_count = 0;
+    }
+
+    pub fn on_frame(&mut self) {
+        if self.mode == RankMode::Fixed {
+            return;
+        }
+
+        self.frame_count += 1;
+        if self.frame_count >= FRAMES_PER_RANK_UP {
+            self.frame_count = 0;
+            self.up();
+        }
+    }
+
+    pub fn on_kill(&mut self) {
+        if self.mode == RankMode::Fixed {
+            return;
+        }
+
+        self.kill_count += 1;
+        if self.kill_count >= KILLS_PER_RANK_UP {
+            self.kill_count = 0;
+            self.up();
+        }
+    }
+
+    pub fn on_death(&mut self) {
+        if self.mode == RankMode::Fixed {
+            return;
+        }
+
+        self.value = self.value.saturating_sub(RANK_DOWN_ON_DEATH);
+        self.kill_count = 0;
+        self.frame_count = 0;
+    }
+
+    fn up(&mut self) {
+        self.value = (self.value + 1).min(RANK_MAX);
+    }
+}
+
+fn deserialize_rank_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    let value = u8::deserialize(deserializer)?;
+    if value > RANK_MAX {
+        return Err(serde::de::Error::custom(format!(
+            "rank must be at most {}",
+            RANK_MAX
+        )));
+    }
+
+    Ok(value)
+}
@@ -0,0 +1,261 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Game;
+use crate::simulation::Simulation;
+use crate::stage::ScheduledSpawner;
+
+pub const RNG_TABLE_LEN: usize = 0x80;
+
+#[rustfmt::skip]
+pub const RNG_TABLE: [u8; RNG_TABLE_LEN] = [
+    // {{{
+    0x1D, 0x9B, 0x9C, 0x2D, 0xD0, 0x54, 0xAF, 0x3E, 0xBF, 0x91, 0x11, 0xCF, 0x75, 0x1F, 0x6D, 0x02,
+    0x19, 0x12, 0xE4, 0x2A, 0xA8, 0xB6, 0x7F, 0x86, 0xE9, 0xEE, 0x4D, 0x9C, 0xE5, 0x8E, 0xE0, 0xB8,
+    0x80, 0x6F, 0x87, 0x1A, 0xA1, 0x57, 0xA8, 0xD5, 0xB1, 0xE9, 0xA0, 0x73, 0x6C, 0x31, 0x50, 0xD2,
+    0x25, 0x92, 0xB0, 0xD9, 0x82, 0x1D, 0x66, 0xFB, 0x0D, 0x94, 0xB8, 0x9D, 0x7B, 0xB1, 0x87, 0xDA,
+    0xB1, 0x61, 0x9E, 0x3A, 0x37, 0x11, 0x6F, 0x5A, 0x1E, 0x47, 0x74, 0x95, 0x0D, 0x1A, 0x57, 0x2C,
+    0x89, 0x44, 0xED, 0x83, 0x45, 0xE0, 0x9D, 0x6A, 0xE1, 0x1B, 0x5B, 0x8C, 0xD5, 0x5B, 0xBF, 0x7A,
+    0xE3, 0xA7, 0x4D, 0xEF, 0x63, 0x5D, 0x65, 0x36, 0xAF, 0x70, 0x34, 0xE5, 0x34, 0xC7, 0xB7, 0x49,
+    0xA3, 0x7C, 0xAB, 0x2A, 0xE0, 0xFD, 0x3E, 0xDC, 0x7C, 0x68, 0x71, 0xBA, 0x20, 0x97, 0x74, 0x73,
+    // }}}
+];
+
+/// `Game::rand` の返す値。
+///
+/// どのモードでも `rng_idx` は呼び出しごとに 1 進む。
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RngMode {
+    /// 実機と同じく `RNG_TABLE` を引く。
+    #[default]
+    Table,
+    /// 常に同じ値を返す。
+    Constant(u8),
+    /// 与えた列を `rng_idx` で (循環して) 引く。
+    Sequence(RngSequence),
+}
+
+impl RngMode {
+    pub fn value(&self, rng_idx: u8) -> u8 {
+        match self {
+            Self::Table => RNG_TABLE[usize::from(rng_idx) % RNG_TABLE_LEN],
+            Self::Constant(value) => *value,
+            Self::Sequence(seq) => seq.0[usize::from(rng_idx) % seq.0.len()],
+        }
+    }
+}
+
+/// `RngMode::Sequence` の列。空でないことを `new` (と読み込み時) に確かめる。
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct RngSequence(Vec<u8>);
+
+impl RngSequence {
+    pub fn new(values: Vec<u8>) -> eyre::Result<Self> {
+        eyre::ensure!(!values.is_empty(), "rng sequence must not be empty");
+
+        Ok(Self(values))
+    }
+
+    pub fn values(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl TryFrom<Vec<u8>> for RngSequence {
+    type Error = eyre::Report;
+
+    fn try_from(values: Vec<u8>) -> eyre::Result<Self> {
+        Self::new(values)
+    }
+}
+
+impl From<RngSequence> for Vec<u8> {
+    fn from(seq: RngSequence) -> Self {
+        seq.0
+    }
+}
+
+/// 開始 RNG index 1 つ分の sweep の結果。
+#[derive(Clone, Debug)]
+pub struct RngSweepResult {
+    pub rng_idx: u8,
+    /// 同じ軌跡になった開始 index には同じ番号を振る (基準の index が 0)。
+    pub path_class: usize,
+    /// 基準の index と軌跡が最初に異なったフレーム。
+    pub first_diff_frame: Option<usize>,
+    pub rand_calls: usize,
+    /// 実行したフレーム数 (エラーで止まった場合は frames より少ない)。
+    pub frames: usize,
+    pub error: Option<String>,
+}
+
+impl std::fmt::Display for RngSweepResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:#04X} path={} rand={}",
+            self.rng_idx, self.path_class, self.rand_calls
+        )?;
+        if let Some(frame) = self.first_diff_frame {
+            write!(f, " diff@{}", frame)?;
+        }
+        if let Some(e) = &self.error {
+            write!(f, " error@{}: {}", self.frames, e)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// GUI で sweep するときのフレーム数の上限。
+pub const RNG_SWEEP_FRAMES_MAX: usize = 18_000;
+
+// (frame, spawn_idx, x, y, sprite_index, state)
+// bytecode::EnemyState は Hash でないので、state は Debug 表記で持つ。
+type PathPoint = (usize, usize, u8, u8, u8, String);
+
+/// 開始 RNG index を 0..128 の全てについて、敵グループ (ステージ) を入力なしで frames フレーム実行する。
+///
+/// game.rng_idx の結果を基準として、他の index の軌跡がどこで分岐するかを調べる。
+/// `step` 1 回で index 1 つ分を実行するので、GUI からは 1 フレームずつ進められる。
+///
+/// 軌跡を丸ごと持つのは基準の index の分だけで、他の index の軌跡はハッシュ値で分類する。
+#[derive(Debug)]
+pub struct RngSweep {
+    game: Game,
+    spawners: Vec<ScheduledSpawner>,
+    frames: usize,
+
+    base_path: Vec<PathPoint>,
+    classes: HashMap<u64, usize>,
+    results: Vec<RngSweepResult>,
+}
+
+impl RngSweep {
+    pub fn new(game: &Game, spawners: &[ScheduledSpawner], frames: usize) -> Self {
+        Self {
+            game: game.clone(),
+            spawners: spawners.to_vec(),
+            frames,
+
+            base_path: vec![],
+            classes: HashMap::new(),
+            results: vec![],
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.results.len() == RNG_TABLE_LEN
+    }
+
+    /// 結果は game.rng_idx から順に並ぶ。
+    pub fn results(&self) -> &[RngSweepResult] {
+        &self.results
+    }
+
+    /// 次の開始 index を 1 つ実行する。全て終わっていれば何もしない。
+    pub fn step(&mut self) {
+        if self.is_done() {
+            return;
+        }
+
+        let is_base = self.results.is_empty();
+        let rng_idx = (self.game.rng_idx & 0x7F).wrapping_add(self.results.len() as u8) & 0x7F;
+
+        let mut sim = Simulation::new(Game {
+            rng_idx,
+            ..self.game.clone()
+        });
+        sim.reset_stage(self.spawners.clone());
+
+        let mut hasher = DefaultHasher::new();
+        let mut path_len = 0;
+        let mut first_diff_frame = None;
+        let mut error = None;
+        for _ in 0..self.frames {
+            if let Err(e) = sim.step() {
+                error = Some(format!("{:?}", e));
+                break;
+            }
+            for enemy in sim.enemy_snapshots() {
+                let point = (
+                    sim.frame_count(),
+                    enemy.spawn_idx,
+                    enemy.x,
+                    enemy.y,
+                    enemy.sprite_index,
+                    format!("{:?}", enemy.state),
+                );
+                point.hash(&mut hasher);
+
+                if is_base {
+                    self.base_path.push(point);
+                } else if first_diff_frame.is_none() {
+                    first_diff_frame = match self.base_path.get(path_len) {
+                        Some(base) if *base != point => Some(base.0.min(point.0)),
+                        Some(_) => None,
+                        None => Some(point.0),
+                    };
+                }
+                path_len += 1;
+            }
+        }
+        if !is_base && first_diff_frame.is_none() {
+            first_diff_frame = self.base_path.get(path_len).map(|point| point.0);
+        }
+
+        let class_count = self.classes.len();
+        let path_class = *self.classes.entry(hasher.finish()).or_insert(class_count);
+
+        self.results.push(RngSweepResult {
+            rng_idx,
+            path_class,
+            first_diff_frame,
+            rand_calls: sim.game_calls().rand_count(),
+            frames: sim.frame_count(),
+            error,
+        });
+    }
+}
+
+/// `RngSweep` を最後まで実行する。
+pub fn rng_sweep(game: &Game, spawners: &[ScheduledSpawner], frames: usize) -> Vec<RngSweepResult> {
+    let mut sweep = RngSweep::new(game, spawners, frames);
+    while !sweep.is_done() {
+        sweep.step();
+    }
+
+    sweep.results
+}
+
+/// sweep 結果の要約 (軌跡の種類数、基準と同じ軌跡になった index の数など)。
+pub fn rng_sweep_summary(results: &[RngSweepResult]) -> String {
+    let classes = results
+        .iter()
+        .map(|r| r.path_class)
+        .max()
+        .map_or(0, |c| c + 1);
+    let same = results.iter().filter(|r| r.path_class == 0).count();
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+    let earliest = results.iter().filter_map(|r| r.first_diff_frame).min();
+
+    let mut summary = format!(
+        "{} distinct paths, {}/{} same as base",
+        classes,
+        same,
+        results.len()
+    );
+    if let Some(frame) = earliest {
+        summary.push_str(&format!(", earliest diff at frame {}", frame));
+    }
+    if errors > 0 {
+        summary.push_str(&format!(", {} errors", errors));
+    }
+
+    summary
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::timeline::Timeline;
+
+/// フォーマットを変更したら上げる。
+pub const SAVE_STATE_VERSION: u32 = 2;
+
+/// GUI の入力欄の内容 (パース前の文字列のまま保持する)。
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EditorFields {
+    // enemy params
+    pub sprite_idx_base: String,
+    pub boss: bool,
+    pub difficulty: String,
+    pub shot_with_rank: bool,
+    pub accel_shot_with_rank: bool,
+    pub homing_shot_with_rank: bool,
+    pub extra_act_with_rank: bool,
+    pub accel_with_rank: bool,
+    pub x_ini: String,
+    pub y_ini: String,
+    pub assembly: String,
+
+    // spawn
+    pub spawn_interval: String,
+    pub spawn_count: String,
+    pub entrypoints: String,
+
+    // env
+    pub second_round: bool,
+    pub stage: String,
+    pub rank: String,
+    /// ランクを `RankMode::Experimental` で変化させる。
+    #[serde(default, alias = "dynamic_rank")]
+    pub experimental_rank: bool,
+    pub rng_idx: String,
+    /// rand の固定値 (`rng_mode_parse` の形式)。空なら固定しない。
+    #[serde(default)]
+    pub rng_pin: String,
+}
+
+impl Default for EditorFields {
+    fn default() -> Self {
+        Self {
+            sprite_idx_base: "0x00".to_owned(),
+            boss: false,
+            difficulty: "0".to_owned(),
+            shot_with_rank: false,
+            accel_shot_with_rank: false,
+            homing_shot_with_rank: false,
+            extra_act_with_rank: false,
+            accel_with_rank: false,
+            x_ini: "0".to_owned(),
+            y_ini: "0".to_owned(),
+            assembly: String::new(),
+
+            spawn_interval: "0".to_owned(),
+            spawn_count: "0".to_owned(),
+            entrypoints: String::new(),
+
+            second_round: false,
+            stage: "1".to_owned(),
+            rank: "0".to_owned(),
+            experimental_rank: false,
+            rng_idx: "0".to_owned(),
+            rng_pin: String::new(),
+        }
+    }
+}
+
+/// プレイグラウンドの全状態。
+///
+/// シミュレーションは `Timeline` (開始時の状態と入力履歴) と現在フレームで表す。
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SaveState {
+    pub version: u32,
+    pub fields: EditorFields,
+    pub timeline: Timeline,
+    pub frame: usize,
+}
+
+impl SaveState {
+    pub fn new(fields: EditorFields, timeline: Timeline, frame: usize) -> Self {
+        Self {
+            version: SAVE_STATE_VERSION,
+            fields,
+            timeline,
+            frame,
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> eyre::Result<()> {
+        let wtr = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(wtr, self)?;
+
+        Ok(())
+    }
+
+    /// 古いバージョンのものは現在の形式に変換して読み込む。
+    pub fn load<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        let rdr = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut state: serde_json::Value = serde_json::from_reader(rdr)?;
+
+        let version = state
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| eyre::eyre!("save state version not found"))?;
+        match version {
+            1 => upgrade_v1(&mut state)?,
+            _ if version == u64::from(SAVE_STATE_VERSION) => {}
+            _ => eyre::bail!("unsupported save state version: {}", version),
+        }
+
+        Ok(serde_json::from_value(state)?)
+    }
+}
+
+/// v1 の `Timeline` は spawner を 1 つ (`spawner: Option<EnemySpawner>`) だけ持っていた。
+/// これをフレーム 0 から動かす `ScheduledSpawner` に置き換える。
+fn upgrade_v1(state: &mut serde_json::Value) -> eyre::Result<()> {
+    let timeline = state
+        .get_mut("timeline")
+        .and_then(serde_json::Value::as_object_mut)
+        .ok_or_else(|| eyre::eyre!("timeline not found"))?;
+
+    let spawners = match timeline.remove("spawner") {
+        None | Some(serde_json::Value::Null) => vec![],
+        Some(spawner) => vec![serde_json::json!({ "frame": 0, "spawner": spawner })],
+    };
+    timeline.insert("spawners".to_owned(), spawners.into());
+    state["version"] = SAVE_STATE_VERSION.into();
+
+    Ok(())
+}
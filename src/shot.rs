@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+const SPEED: f32 = 6.;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Shot {
+    x: f32,
+    y: f32,
+}
+
+impl Shot {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn step(&mut self) {
+        self.y -= SPEED;
+    }
+
+    pub fn is_on_screen(&self) -> bool {
+        self.y >= 0.
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+}
@@ -0,0 +1,336 @@
+use starsoldier_bytecode as bytecode;
+
+use crate::audio::SoundLogEntry;
+use crate::breakpoint::TrapDivergence;
+use crate::calllog::{CallLog, GameCall, GameCallKind, InstrumentedGame};
+use crate::enemy::{Enemy, EnemySpawner};
+use crate::game::Game;
+use crate::hero::{Hero, HeroInput, HERO_X_INI, HERO_Y_INI};
+use crate::stage::ScheduledSpawner;
+
+/// 描画系に依存しないシミュレーション本体。
+#[derive(Debug)]
+pub struct Simulation {
+    game: Game,
+    spawners: Vec<ScheduledSpawner>,
+    enemys: Vec<Enemy>,
+    snapshots: Vec<EnemySnapshot>,
+    sound_log: Vec<SoundLogEntry>,
+    calls: CallLog,
+    trap_divergences: Vec<TrapDivergence>,
+
+    spawn_count: usize,
+    frame_count: usize,
+}
+
+/// 敵が 1 体もいないときの `Simulation` の状態。
+///
+/// `bytecode::Interpreter` は複製できないので、敵がいるときの状態は保存できない。
+/// `Game` の呼び出しログは集計値だけを持つ (復元後のログはその時点から始まる)。
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    game: Game,
+    spawners: Vec<ScheduledSpawner>,
+    sound_log: Vec<SoundLogEntry>,
+    calls: CallLog,
+    trap_divergences: Vec<TrapDivergence>,
+
+    spawn_count: usize,
+    frame_count: usize,
+}
+
+#[derive(Debug)]
+pub struct EnemySnapshot {
+    pub spawn_idx: usize,
+    pub entrypoint: usize,
+    pub x: u8,
+    pub y: u8,
+    pub sprite_index: u8,
+    pub state: bytecode::EnemyState,
+}
+
+impl Simulation {
+    pub fn new(game: Game) -> Self {
+        Self {
+            game,
+            spawners: vec![],
+            enemys: vec![],
+            snapshots: vec![],
+            sound_log: vec![],
+            calls: CallLog::default(),
+            trap_divergences: vec![],
+
+            spawn_count: 0,
+            frame_count: 0,
+        }
+    }
+
+    /// checkpoint の時点の状態を復元する。
+    pub fn from_checkpoint(checkpoint: Checkpoint) -> Self {
+        Self {
+            game: checkpoint.game,
+            spawners: checkpoint.spawners,
+            enemys: vec![],
+            snapshots: vec![],
+            sound_log: checkpoint.sound_log,
+            calls: checkpoint.calls,
+            trap_divergences: checkpoint.trap_divergences,
+
+            spawn_count: checkpoint.spawn_count,
+            frame_count: checkpoint.frame_count,
+        }
+    }
+
+    /// 現在の状態を保存する。敵 (直前のフレームで消えたものも含む) がいれば None。
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        if !self.enemys.is_empty() || !self.snapshots.is_empty() {
+            return None;
+        }
+
+        Some(Checkpoint {
+            game: self.game.clone(),
+            spawners: self.spawners.clone(),
+            sound_log: self.sound_log.clone(),
+            calls: self.calls.summary(),
+            trap_divergences: self.trap_divergences.clone(),
+
+            spawn_count: self.spawn_count,
+            frame_count: self.frame_count,
+        })
+    }
+
+    /// 敵と弾を全て消し、新たな spawner で最初から実行し直す。
+    ///
+    /// ランクは spawner の初期ランクに戻す。
+    pub fn reset(&mut self, spawner: Option<EnemySpawner>) {
+        self.reset_stage(
+            spawner
+                .into_iter()
+                .map(|spawner| ScheduledSpawner { frame: 0, spawner })
+                .collect(),
+        );
+    }
+
+    /// 複数の spawner (ステージ) で最初から実行し直す。
+    ///
+    /// 各 spawner は指定フレームから動き出し、全ての敵が 1 つの `Game` を共有する
+    /// (乱数も敵の処理順に消費される)。ランクは最初の spawner の初期ランクに戻す。
+    /// 自機は初期位置に戻し、曲はステージ曲に戻す。
+    pub fn reset_stage(&mut self, spawners: Vec<ScheduledSpawner>) {
+        if let Some(scheduled) = spawners.first() {
+            self.game.rank.reset(scheduled.spawner.rank());
+        }
+        self.spawners = spawners;
+        self.enemys.clear();
+        self.snapshots.clear();
+        self.sound_log.clear();
+        self.calls.clear();
+        self.trap_divergences.clear();
+        self.spawn_count = 0;
+        self.game.take_sound_events();
+        self.game.hero = Hero::new(HERO_X_INI, HERO_Y_INI);
+        self.game.music = None;
+        self.game.bullets.clear();
+        self.game.shots.clear();
+
+        self.frame_count = 0;
+    }
+
+    pub fn step(&mut self) -> bytecode::InterpretResult<()> {
+        self.step_with_input(HeroInput::default())
+    }
+
+    pub fn step_with_input(&mut self, input: HeroInput) -> bytecode::InterpretResult<()> {
+        self.game.step_hero(input);
+
+        // spawn index はグループをまたいで出現順に振る。
+        for scheduled in &mut self.spawners {
+            if scheduled.frame > self.frame_count {
+                continue;
+            }
+            if let Some(mut enemy) = scheduled.spawner.step(self.game.rank.value()) {
+                enemy.set_spawn_index(self.spawn_count);
+                self.spawn_count += 1;
+                self.enemys.push(enemy);
+            }
+        }
+
+        let frame = self.frame_count;
+        for enemy in &mut self.enemys {
+            let spawn_idx = enemy.spawn_index();
+            let start = self.calls.total();
+            enemy.step(&mut InstrumentedGame::new(
+                &mut self.game,
+                frame,
+                spawn_idx,
+                &mut self.calls,
+            ))?;
+            follow_traps(
+                enemy,
+                frame,
+                false,
+                start,
+                &mut self.calls,
+                &mut self.trap_divergences,
+            );
+        }
+
+        let calls = &mut self.calls;
+        let trap_divergences = &mut self.trap_divergences;
+        self.game.step_shots(&mut self.enemys, |game, enemy| {
+            let spawn_idx = enemy.spawn_index();
+            let start = calls.total();
+            enemy.damage(&mut InstrumentedGame::new(game, frame, spawn_idx, calls));
+            follow_traps(enemy, frame, true, start, calls, trap_divergences);
+        });
+        self.snapshots = self.enemys.iter().map(EnemySnapshot::new).collect();
+        self.retain_alive_enemys();
+
+        self.game.step_bullets();
+        self.game.check_hero_collision(&self.enemys);
+        self.game.rank.on_frame();
+
+        self.frame_count += 1;
+        self.log_sound_events();
+
+        Ok(())
+    }
+
+    /// 点 (x, y) にいる敵 (最も手前のもの) にダメージを与える。
+    pub fn damage_enemy_at(&mut self, x: f32, y: f32) {
+        if let Some(enemy) = self
+            .enemys
+            .iter_mut()
+            .rev()
+            .find(|enemy| enemy.contains(x, y))
+        {
+            let spawn_idx = enemy.spawn_index();
+            let start = self.calls.total();
+            enemy.damage(&mut InstrumentedGame::new(
+                &mut self.game,
+                self.frame_count,
+                spawn_idx,
+                &mut self.calls,
+            ));
+            follow_traps(
+                enemy,
+                self.frame_count,
+                true,
+                start,
+                &mut self.calls,
+                &mut self.trap_divergences,
+            );
+            if !matches!(enemy.state(), bytecode::EnemyState::Alive) {
+                self.game.rank.on_kill();
+            }
+        }
+        self.retain_alive_enemys();
+        self.log_sound_events();
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    pub fn spawners(&self) -> &[ScheduledSpawner] {
+        &self.spawners
+    }
+
+    pub fn enemys(&self) -> &[Enemy] {
+        &self.enemys
+    }
+
+    /// 直前の `step` で処理した敵のスナップショットを返す。
+    ///
+    /// そのフレームで死亡/退場した敵も含む。
+    pub fn enemy_snapshots(&self) -> &[EnemySnapshot] {
+        &self.snapshots
+    }
+
+    /// これまでに発生したサウンドイベント (フレーム番号は `frame_count` と同じ基準)。
+    pub fn sound_log(&self) -> &[SoundLogEntry] {
+        &self.sound_log
+    }
+
+    /// インタプリタから `Game` への呼び出しの記録 (最新の `CALL_LOG_MAX` 件)。
+    pub fn game_calls(&self) -> &CallLog {
+        &self.calls
+    }
+
+    /// トラップを挿入したプログラムが元のプログラムと食い違った敵。
+    pub fn trap_divergences(&self) -> &[TrapDivergence] {
+        &self.trap_divergences
+    }
+
+    fn log_sound_events(&mut self) {
+        let frame = self.frame_count;
+        self.sound_log.extend(
+            self.game
+                .take_sound_events()
+                .into_iter()
+                .map(|event| SoundLogEntry { frame, event }),
+        );
+    }
+
+    fn retain_alive_enemys(&mut self) {
+        self.enemys
+            .retain(|enemy| matches!(enemy.state(), bytecode::EnemyState::Alive));
+    }
+}
+
+/// enemy の直前の `step`/`damage` (calls の start 以降の呼び出し) をトラップを挿入した
+/// プログラムでもなぞり、踏んだトラップを calls に記録する。
+fn follow_traps(
+    enemy: &mut Enemy,
+    frame: usize,
+    damage: bool,
+    start: usize,
+    calls: &mut CallLog,
+    trap_divergences: &mut Vec<TrapDivergence>,
+) {
+    if !enemy.has_traps() {
+        return;
+    }
+
+    let spawn_idx = enemy.spawn_index();
+    let kinds: Vec<_> = calls.since(start).map(|call| call.kind).collect();
+    match enemy.step_traps(&kinds, damage) {
+        Some(traps) => {
+            for index in traps {
+                calls.push(GameCall {
+                    frame,
+                    spawn_idx,
+                    kind: GameCallKind::Trap { index },
+                });
+            }
+        }
+        None => trap_divergences.push(TrapDivergence { frame, spawn_idx }),
+    }
+}
+
+impl Checkpoint {
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+}
+
+impl EnemySnapshot {
+    fn new(enemy: &Enemy) -> Self {
+        Self {
+            spawn_idx: enemy.spawn_index(),
+            entrypoint: enemy.entrypoint(),
+            x: enemy.x(),
+            y: enemy.y(),
+            sprite_index: enemy.sprite_index(),
+            state: enemy.state(),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::ops::Range;
+
+use starsoldier_bytecode as bytecode;
+
+/// アセンブリの各行とバイトコード上のアドレスの対応。
+///
+/// `bytecode::asm` は行ごとの情報を返さないので、先頭から各行までを
+/// 繰り返しアセンブルしてアドレスを求める。前方参照されるラベルは
+/// 末尾にまとめて定義しておく (命令長はラベルの値に依存しない)。
+#[derive(Debug)]
+pub struct SourceMap {
+    lines: Vec<SourceLine>,
+    labels: Vec<(String, usize)>,
+    program: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SourceLine {
+    pub text: String,
+    pub addrs: Range<usize>,
+}
+
+/// 逆アセンブルリストの 1 命令。
+#[derive(Debug)]
+pub struct ListingRow {
+    pub addr: usize,
+    pub bytes: Vec<u8>,
+    pub text: String,
+    /// この命令のアドレスを指すラベル。
+    pub labels: Vec<String>,
+    /// 行番号 (0-based)。
+    pub line: usize,
+}
+
+impl SourceMap {
+    pub fn build(assembly: &str) -> eyre::Result<Self> {
+        let texts: Vec<&str> = assembly.lines().collect();
+        let label_defs: Vec<Option<&str>> = texts.iter().map(|text| label_def(text)).collect();
+
+        let mut lines = Vec::with_capacity(texts.len());
+        let mut labels = vec![];
+        let mut addr = 0;
+        for (i, text) in texts.iter().enumerate() {
+            let end = assembled_len(&texts[..=i], &label_defs[i + 1..])?;
+
+            if let Some(name) = label_defs[i] {
+                labels.push((name.to_owned(), addr));
+            }
+            lines.push(SourceLine {
+                text: (*text).to_owned(),
+                addrs: addr..end,
+            });
+
+            addr = end;
+        }
+
+        let program = bytecode::asm(assembly.as_bytes())?;
+
+        Ok(Self {
+            lines,
+            labels,
+            program,
+        })
+    }
+
+    pub fn lines(&self) -> &[SourceLine] {
+        &self.lines
+    }
+
+    pub fn labels(&self) -> &[(String, usize)] {
+        &self.labels
+    }
+
+    pub fn label_addr(&self, name: &str) -> Option<usize> {
+        self.labels
+            .iter()
+            .find(|(label, _)| label == name)
+            .map(|&(_, addr)| addr)
+    }
+
+    pub fn program(&self) -> &[u8] {
+        &self.program
+    }
+
+    /// 命令を含む行ごとに、アドレス、バイト列、ソース、ラベルを並べる。
+    pub fn listing(&self) -> Vec<ListingRow> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.addrs.is_empty())
+            .map(|(i, line)| ListingRow {
+                addr: line.addrs.start,
+                bytes: self.program[line.addrs.clone()].to_vec(),
+                text: line.text.trim().to_owned(),
+                labels: self
+                    .labels
+                    .iter()
+                    .filter(|(_, addr)| *addr == line.addrs.start)
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+                line: i,
+            })
+            .collect()
+    }
+
+    /// addr を含む命令の行番号 (0-based) を返す。
+    pub fn line_of_addr(&self, addr: usize) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| line.addrs.contains(&addr))
+    }
+}
+
+/// アセンブルエラー。
+///
+/// `bytecode::asm` は位置を返さないので、行番号 (0-based) だけを持つ。
+#[derive(Clone, Debug)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// アセンブリ中のエラーを全て列挙する。
+///
+/// `bytecode::asm` は最初のエラーしか返さず位置も持たないので、`SourceMap::build`
+/// と同様に先頭から 1 行ずつ追加してアセンブルし、失敗した行をエラーとして
+/// 取り除きながら進める。
+pub fn asm_errors(assembly: &str) -> Vec<AsmError> {
+    if bytecode::asm(assembly.as_bytes()).is_ok() {
+        return vec![];
+    }
+
+    let texts: Vec<&str> = assembly.lines().collect();
+    let label_defs: Vec<Option<&str>> = texts.iter().map(|text| label_def(text)).collect();
+
+    let mut errors = vec![];
+    let mut ok_texts = vec![];
+    for (i, text) in texts.iter().enumerate() {
+        ok_texts.push(*text);
+        if let Err(e) = assembled_len(&ok_texts, &label_defs[i + 1..]) {
+            ok_texts.pop();
+            errors.push(AsmError {
+                line: i,
+                message: e.to_string(),
+            });
+        }
+    }
+
+    // 行単位では問題ないが全体として失敗する場合 (未定義ラベルの参照など)。
+    if errors.is_empty() {
+        if let Err(e) = bytecode::asm(assembly.as_bytes()) {
+            errors.push(AsmError {
+                line: texts.len().saturating_sub(1),
+                message: e.to_string(),
+            });
+        }
+    }
+
+    errors
+}
+
+/// 行頭のラベル定義 `NAME:` を取り出し、(ラベル名, 残りの部分) を返す。
+///
+/// `NAME: move 0x19` のように、ラベルと同じ行に命令を書いてもよい。
+pub(crate) fn split_label(text: &str) -> (Option<&str>, &str) {
+    let trimmed = text.trim();
+    let (name, rest) = match trimmed.split_once(':') {
+        Some((name, rest)) => (name, rest.trim()),
+        None => return (None, trimmed),
+    };
+
+    let mut chars = name.chars();
+    let head_ok = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_');
+    if head_ok && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        (Some(name), rest)
+    } else {
+        (None, trimmed)
+    }
+}
+
+fn label_def(text: &str) -> Option<&str> {
+    split_label(text).0
+}
+
+fn assembled_len(texts: &[&str], rest_label_defs: &[Option<&str>]) -> eyre::Result<usize> {
+    let mut src = texts.join("\n");
+    for name in rest_label_defs.iter().flatten() {
+        src.push('\n');
+        src.push_str(name);
+        src.push(':');
+    }
+    src.push('\n');
+
+    Ok(bytecode::asm(src.as_bytes())?.len())
+}
@@ -0,0 +1,221 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use starsoldier_bytecode as bytecode;
+
+use crate::enemy::{EnemyInit, EnemySpawner};
+use crate::parse::{entrypoints_resolve, Entrypoint};
+use crate::preset::PresetFile;
+
+/// 何フレーム目から動かすかを指定した spawner。
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScheduledSpawner {
+    pub frame: usize,
+    pub spawner: EnemySpawner,
+}
+
+/// ステージの敵グループの出現スケジュール。TOML で書く。
+///
+/// ```toml
+/// name = "stage 1"
+/// scroll_speed = 1.0
+///
+/// [[groups]]
+/// frame = 0
+/// preset = 0x01
+///
+/// [[groups]]
+/// scroll = 480
+/// preset = 0x05
+/// x = 64
+/// entrypoints = ["L07", "L3D"]
+/// spawn_count = 2
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageSchedule {
+    #[serde(default)]
+    pub name: String,
+    /// 1 フレームあたりのスクロール量 (ピクセル)。`scroll` で指定したグループの出現フレームの計算に使う。
+    #[serde(default = "default_scroll_speed")]
+    pub scroll_speed: f32,
+    pub groups: Vec<StageGroup>,
+}
+
+/// スケジュール中の敵グループ 1 つ。preset の値を必要なものだけ上書きする。
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StageGroup {
+    #[serde(flatten)]
+    pub at: StageTrigger,
+    pub preset: u8,
+    #[serde(flatten)]
+    pub overrides: GroupOverrides,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageTrigger {
+    Frame(usize),
+    /// ステージ開始からのスクロール量 (ピクセル)。
+    Scroll(u32),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct GroupOverrides {
+    pub spawn_interval: Option<usize>,
+    pub spawn_count: Option<usize>,
+    pub sprite_idx_base: Option<u8>,
+    pub boss: Option<bool>,
+    pub difficulty: Option<u8>,
+    pub shot_with_rank: Option<bool>,
+    pub accel_shot_with_rank: Option<bool>,
+    pub homing_shot_with_rank: Option<bool>,
+    pub extra_act_with_rank: Option<bool>,
+    pub accel_with_rank: Option<bool>,
+    pub x: Option<u8>,
+    pub y: Option<u8>,
+    pub entrypoints: Option<Vec<Entrypoint>>,
+}
+
+/// `StageGroup` に書けるキー。
+///
+/// flatten したフィールドには deny_unknown_fields が効かないので、綴りの誤りはこれで検出する。
+const GROUP_KEYS: &[&str] = &[
+    "frame",
+    "scroll",
+    "preset",
+    "spawn_interval",
+    "spawn_count",
+    "sprite_idx_base",
+    "boss",
+    "difficulty",
+    "shot_with_rank",
+    "accel_shot_with_rank",
+    "homing_shot_with_rank",
+    "extra_act_with_rank",
+    "accel_with_rank",
+    "x",
+    "y",
+    "entrypoints",
+];
+
+fn default_scroll_speed() -> f32 {
+    1.
+}
+
+impl StageTrigger {
+    pub fn frame(&self, scroll_speed: f32) -> usize {
+        match *self {
+            Self::Frame(frame) => frame,
+            Self::Scroll(scroll) => (scroll as f32 / scroll_speed).ceil() as usize,
+        }
+    }
+}
+
+impl StageSchedule {
+    pub fn load<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// TOML をパースする。グループに未知のキーがあればエラーとする。
+    pub fn parse(text: &str) -> eyre::Result<Self> {
+        let value: toml::Value = toml::from_str(text)?;
+        let groups = value.get("groups").and_then(toml::Value::as_array);
+        for (i, group) in groups.into_iter().flatten().enumerate() {
+            let table = match group.as_table() {
+                Some(table) => table,
+                None => continue,
+            };
+            if let Some(key) = table.keys().find(|key| !GROUP_KEYS.contains(&key.as_str())) {
+                eyre::bail!("group {}: unknown key: {}", i, key);
+            }
+            eyre::ensure!(
+                table.contains_key("frame") != table.contains_key("scroll"),
+                "group {}: exactly one of frame and scroll is required",
+                i
+            );
+        }
+
+        let schedule: Self = value.try_into()?;
+        eyre::ensure!(schedule.scroll_speed > 0., "scroll_speed must be positive");
+
+        Ok(schedule)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> eyre::Result<()> {
+        std::fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// 各グループの spawner を出現フレーム順に作る。全ての敵は rank で初期化する。
+    ///
+    /// preset id が重複する場合は presets の後ろにあるもの (ユーザー preset) を優先する。
+    pub fn build(&self, presets: &[PresetFile], rank: u8) -> eyre::Result<Vec<ScheduledSpawner>> {
+        let mut spawners = self
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(i, group)| {
+                let spawner = group.spawner(presets, rank).map_err(|e| {
+                    eyre::eyre!("group {} (preset {:#04X}): {}", i, group.preset, e)
+                })?;
+                Ok(ScheduledSpawner {
+                    frame: group.at.frame(self.scroll_speed),
+                    spawner,
+                })
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        // 同じフレームのものはスケジュールに書いた順に動かす。
+        spawners.sort_by_key(|scheduled| scheduled.frame);
+
+        Ok(spawners)
+    }
+}
+
+impl StageGroup {
+    fn spawner(&self, presets: &[PresetFile], rank: u8) -> eyre::Result<EnemySpawner> {
+        let preset = presets
+            .iter()
+            .rev()
+            .find(|preset| preset.id == self.preset)
+            .ok_or_else(|| eyre::eyre!("preset not found"))?;
+        let o = &self.overrides;
+
+        let program = bytecode::asm(preset.assembly.as_bytes())?;
+        let spawn_count = o.spawn_count.unwrap_or(preset.spawn_count);
+        let entrypoints = o.entrypoints.as_ref().unwrap_or(&preset.entrypoints);
+        eyre::ensure!(
+            entrypoints.len() == spawn_count,
+            "entrypoint count mismatch"
+        );
+        let entrypoints = entrypoints_resolve(entrypoints, &preset.assembly)?;
+
+        let enemy_init = EnemyInit {
+            sprite_idx_base: o.sprite_idx_base.unwrap_or(preset.sprite_idx_base),
+            program,
+            boss: o.boss.unwrap_or(preset.boss),
+            difficulty: o.difficulty.unwrap_or(preset.difficulty),
+            shot_with_rank: o.shot_with_rank.unwrap_or(preset.shot_with_rank),
+            accel_shot_with_rank: o
+                .accel_shot_with_rank
+                .unwrap_or(preset.accel_shot_with_rank),
+            homing_shot_with_rank: o
+                .homing_shot_with_rank
+                .unwrap_or(preset.homing_shot_with_rank),
+            extra_act_with_rank: o.extra_act_with_rank.unwrap_or(preset.extra_act_with_rank),
+            accel_with_rank: o.accel_with_rank.unwrap_or(preset.accel_with_rank),
+            rank,
+            x: o.x.unwrap_or(preset.x_ini),
+            y: o.y.unwrap_or(preset.y_ini),
+        };
+
+        Ok(EnemySpawner::new(
+            o.spawn_interval.unwrap_or(preset.spawn_interval),
+            spawn_count,
+            &entrypoints,
+            enemy_init,
+        ))
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::Game;
+use crate::hero::HeroInput;
+use crate::simulation::{Checkpoint, Simulation};
+use crate::stage::ScheduledSpawner;
+
+/// 記録中にチェックポイントを取る間隔 (フレーム)。
+pub const CHECKPOINT_INTERVAL: usize = 600;
+
+/// シミュレーションの全履歴。任意のフレームの状態を復元できる。
+///
+/// `bytecode::Interpreter` は複製できないので、状態そのものではなく
+/// 開始時の `Game`/spawner と以後の入力を記録しておき、
+/// 復元時には再実行する (シミュレーションは決定的)。
+///
+/// 再実行を短くするため、`CHECKPOINT_INTERVAL` フレームごとにチェックポイントを取り、
+/// 復元時はその直前のチェックポイントから再実行する。ただし敵がいる間の状態は
+/// 保存できないので、チェックポイントは間隔を過ぎた後の最初の敵がいないフレームで
+/// 取る (敵が途切れない区間が長いと、その間は最初から再実行するのと変わらない)。
+/// チェックポイントは保存しない。
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Timeline {
+    game: Game,
+    spawners: Vec<ScheduledSpawner>,
+
+    events: Vec<TimelineEvent>,
+    frame_count: usize,
+
+    // (その時点までのイベント数, 状態)。イベント数の昇順。
+    #[serde(skip)]
+    checkpoints: Vec<(usize, Checkpoint)>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum TimelineEvent {
+    Step(HeroInput),
+    DamageAt(f32, f32),
+}
+
+impl Timeline {
+    /// `Simulation::reset` の直後の状態を起点とする。
+    pub fn new(sim: &Simulation) -> Self {
+        assert_eq!(sim.frame_count(), 0);
+        assert!(sim.enemys().is_empty());
+
+        Self {
+            game: sim.game().clone(),
+            spawners: sim.spawners().to_vec(),
+
+            events: vec![],
+            frame_count: 0,
+
+            checkpoints: vec![],
+        }
+    }
+
+    /// 記録されているフレーム数。
+    pub fn len(&self) -> usize {
+        self.frame_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frame_count == 0
+    }
+
+    /// 開始時の `Game`。
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// 開始時の spawner。
+    pub fn spawners(&self) -> &[ScheduledSpawner] {
+        &self.spawners
+    }
+
+    /// 開始時の spawner を書き換える (ブレークポイントのトラップの設定など)。
+    ///
+    /// 動きが変わりうるので、チェックポイントは捨てる。
+    pub fn spawners_mut(&mut self) -> &mut [ScheduledSpawner] {
+        self.checkpoints.clear();
+        &mut self.spawners
+    }
+
+    pub fn events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    /// sim は input でそのフレームを実行した直後のもの。
+    pub fn record_step(&mut self, input: HeroInput, sim: &Simulation) {
+        self.events.push(TimelineEvent::Step(input));
+        self.frame_count += 1;
+
+        let last_frame = self
+            .checkpoints
+            .last()
+            .map_or(0, |(_, checkpoint)| checkpoint.frame_count());
+        if self.frame_count >= last_frame + CHECKPOINT_INTERVAL {
+            if let Some(checkpoint) = sim.checkpoint() {
+                self.checkpoints.push((self.events.len(), checkpoint));
+            }
+        }
+    }
+
+    pub fn record_damage(&mut self, x: f32, y: f32) {
+        self.events.push(TimelineEvent::DamageAt(x, y));
+    }
+
+    /// frame より後の記録を捨てる。巻き戻した地点から実行を再開するときに使う。
+    pub fn truncate(&mut self, frame: usize) {
+        self.events.truncate(self.event_count_until(frame));
+        self.frame_count = self.frame_count.min(frame);
+
+        let event_count = self.events.len();
+        self.checkpoints.retain(|&(i, _)| i <= event_count);
+    }
+
+    /// frame 時点の状態を、その直前のチェックポイント (なければ最初) からの再実行により復元する。
+    pub fn seek(&self, frame: usize) -> eyre::Result<Simulation> {
+        let event_count = self.event_count_until(frame);
+
+        let (start, mut sim) = match self
+            .checkpoints
+            .iter()
+            .rev()
+            .find(|(i, _)| *i <= event_count)
+        {
+            Some((i, checkpoint)) => (*i, Simulation::from_checkpoint(checkpoint.clone())),
+            None => {
+                let mut sim = Simulation::new(self.game.clone());
+                sim.reset_stage(self.spawners.clone());
+                (0, sim)
+            }
+        };
+
+        for event in &self.events[start..event_count] {
+            match *event {
+                TimelineEvent::Step(input) => sim.step_with_input(input)?,
+                TimelineEvent::DamageAt(x, y) => sim.damage_enemy_at(x, y),
+            }
+        }
+
+        Ok(sim)
+    }
+
+    // frame 時点までに発生したイベント数。
+    // (frame + 1) 個目の Step の直前までを含む。
+    fn event_count_until(&self, frame: usize) -> usize {
+        let mut steps = 0;
+        self.events
+            .iter()
+            .position(|event| {
+                if let TimelineEvent::Step(_) = event {
+                    steps += 1;
+                }
+                steps > frame
+            })
+            .unwrap_or(self.events.len())
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::Write;
+
+use starsoldier_bytecode as bytecode;
+
+use crate::simulation::Simulation;
+
+#[derive(Debug)]
+pub struct TraceRow {
+    pub frame: usize,
+    pub spawn_idx: usize,
+    pub entrypoint: usize,
+    pub x: u8,
+    pub y: u8,
+    pub sprite_index: u8,
+    pub state: bytecode::EnemyState,
+    pub rng_idx: u8,
+}
+
+/// 敵の軌跡をフレームごとに記録する。
+#[derive(Debug, Default)]
+pub struct TraceRecorder {
+    rows: Vec<TraceRow>,
+}
+
+impl TraceRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `Simulation::step` の直後に呼ぶ。
+    pub fn record(&mut self, sim: &Simulation) {
+        let frame = sim.frame_count();
+        let rng_idx = sim.game().rng_idx;
+
+        self.rows
+            .extend(sim.enemy_snapshots().iter().map(|enemy| TraceRow {
+                frame,
+                spawn_idx: enemy.spawn_idx,
+                entrypoint: enemy.entrypoint,
+                x: enemy.x,
+                y: enemy.y,
+                sprite_index: enemy.sprite_index,
+                state: enemy.state,
+                rng_idx,
+            }));
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    /// frame より後の記録を捨てる (巻き戻し用)。
+    pub fn truncate(&mut self, frame: usize) {
+        self.rows.retain(|row| row.frame <= frame);
+    }
+
+    pub fn rows(&self) -> &[TraceRow] {
+        &self.rows
+    }
+
+    pub fn write_csv<W: Write>(&self, mut wtr: W) -> std::io::Result<()> {
+        writeln!(
+            wtr,
+            "frame,spawn_idx,entrypoint,x,y,sprite_index,state,rng_idx"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                wtr,
+                "{},{},{},{},{},{},{:?},{}",
+                row.frame,
+                row.spawn_idx,
+                row.entrypoint,
+                row.x,
+                row.y,
+                row.sprite_index,
+                row.state,
+                row.rng_idx
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_json<W: Write>(&self, mut wtr: W) -> std::io::Result<()> {
+        writeln!(wtr, "[")?;
+        for (i, row) in self.rows.iter().enumerate() {
+            let sep = if i + 1 == self.rows.len() { "" } else { "," };
+            writeln!(
+                wtr,
+                r#"  {{"frame":{},"spawn_idx":{},"entrypoint":{},"x":{},"y":{},"sprite_index":{},"state":"{:?}","rng_idx":{}}}{}"#,
+                row.frame,
+                row.spawn_idx,
+                row.entrypoint,
+                row.x,
+                row.y,
+                row.sprite_index,
+                row.state,
+                row.rng_idx,
+                sep
+            )?;
+        }
+        writeln!(wtr, "]")?;
+
+        Ok(())
+    }
+}
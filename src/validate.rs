@@ -0,0 +1,230 @@
+use starsoldier_bytecode as bytecode;
+
+use crate::enemy::{EnemyInit, EnemySpawner};
+use crate::game::Game;
+use crate::parse::{
+    entrypoints_parse, entrypoints_resolve, rank_parse, rng_mode_parse, stage_parse,
+};
+use crate::rank::RankMode;
+use crate::rng::RngMode;
+use crate::savestate::EditorFields;
+use crate::srcmap::{asm_errors, AsmError};
+
+/// GUI の入力欄。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FieldId {
+    SpriteIdxBase,
+    Difficulty,
+    XIni,
+    YIni,
+    SpawnInterval,
+    SpawnCount,
+    Entrypoints,
+    Stage,
+    Rank,
+    RngIdx,
+    RngPin,
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldError {
+    pub field: FieldId,
+    pub message: String,
+}
+
+/// `EditorFields::build` のエラー。全ての入力欄とアセンブリのエラーをまとめて持つ。
+#[derive(Clone, Debug, Default)]
+pub struct BuildErrors {
+    pub fields: Vec<FieldError>,
+    pub asm: Vec<AsmError>,
+    /// 入力欄やアセンブリの行に対応しないエラー (ステージや preset の読み込みなど)。
+    pub other: Vec<String>,
+}
+
+impl BuildErrors {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.asm.is_empty() && self.other.is_empty()
+    }
+
+    pub fn field(&self, field: FieldId) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|e| e.field == field)
+            .map(|e| e.message.as_str())
+    }
+}
+
+impl std::fmt::Display for BuildErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut sep = "";
+        for e in &self.fields {
+            write!(f, "{}{:?}: {}", sep, e.field, e.message)?;
+            sep = "; ";
+        }
+        for e in &self.asm {
+            write!(f, "{}{}: {}", sep, e.line + 1, e.message)?;
+            sep = "; ";
+        }
+        for message in &self.other {
+            write!(f, "{}{}", sep, message)?;
+            sep = "; ";
+        }
+
+        Ok(())
+    }
+}
+
+// 結果がエラーなら errors に追加して None を返す。
+macro_rules! check {
+    ($errors:expr, $field:expr, $res:expr) => {{
+        match $res {
+            Ok(x) => Some(x),
+            Err(e) => {
+                $errors.fields.push(FieldError {
+                    field: $field,
+                    message: e.to_string(),
+                });
+                None
+            }
+        }
+    }};
+}
+
+/// spawner 以外に入力欄から設定する `Game` の状態。
+#[derive(Clone, Debug)]
+pub struct PlayEnv {
+    pub second_round: bool,
+    pub stage: u8,
+    pub rng_idx: u8,
+    pub rng_mode: RngMode,
+    /// 初期ランク。`Simulation::reset` では spawner の初期ランクが使われるので、
+    /// spawner を作るときに渡すこと。
+    pub rank: u8,
+    pub rank_mode: RankMode,
+}
+
+impl PlayEnv {
+    pub fn apply(&self, game: &mut Game) {
+        game.second_round = self.second_round;
+        game.stage = self.stage;
+        game.rng_idx = self.rng_idx;
+        game.rng_mode = self.rng_mode.clone();
+        game.rank.mode = self.rank_mode;
+    }
+}
+
+impl EditorFields {
+    /// 入力欄を全て検証し、spawner と環境を作る。
+    pub fn build(&self) -> Result<(EnemySpawner, PlayEnv), BuildErrors> {
+        let mut errors = BuildErrors::default();
+
+        let sprite_idx_base = check!(
+            errors,
+            FieldId::SpriteIdxBase,
+            parse_int::parse::<u8>(&self.sprite_idx_base)
+        );
+        let difficulty = check!(
+            errors,
+            FieldId::Difficulty,
+            parse_int::parse::<u8>(&self.difficulty)
+        );
+        let x_ini = check!(errors, FieldId::XIni, parse_int::parse::<u8>(&self.x_ini));
+        let y_ini = check!(errors, FieldId::YIni, parse_int::parse::<u8>(&self.y_ini));
+        let spawn_interval = check!(
+            errors,
+            FieldId::SpawnInterval,
+            parse_int::parse::<usize>(&self.spawn_interval)
+        );
+        let spawn_count = check!(
+            errors,
+            FieldId::SpawnCount,
+            parse_int::parse::<usize>(&self.spawn_count)
+        );
+        let entrypoints = spawn_count.and_then(|count| {
+            check!(
+                errors,
+                FieldId::Entrypoints,
+                entrypoints_parse(&self.entrypoints, count)
+            )
+        });
+        let env = self.check_env(&mut errors);
+
+        let program = match bytecode::asm(self.assembly.as_bytes()) {
+            Ok(program) => Some(program),
+            Err(_) => {
+                errors.asm = asm_errors(&self.assembly);
+                None
+            }
+        };
+        // ラベルはアセンブルできたときだけ解決する。
+        let entrypoints = match (entrypoints, &program) {
+            (Some(entrypoints), Some(_)) => check!(
+                errors,
+                FieldId::Entrypoints,
+                entrypoints_resolve(&entrypoints, &self.assembly)
+            ),
+            _ => None,
+        };
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let env = env.unwrap();
+
+        let enemy_init = EnemyInit {
+            sprite_idx_base: sprite_idx_base.unwrap(),
+            program: program.unwrap(),
+            boss: self.boss,
+            difficulty: difficulty.unwrap(),
+            shot_with_rank: self.shot_with_rank,
+            accel_shot_with_rank: self.accel_shot_with_rank,
+            homing_shot_with_rank: self.homing_shot_with_rank,
+            extra_act_with_rank: self.extra_act_with_rank,
+            accel_with_rank: self.accel_with_rank,
+            rank: env.rank,
+            x: x_ini.unwrap(),
+            y: y_ini.unwrap(),
+        };
+        let spawner = EnemySpawner::new(
+            spawn_interval.unwrap(),
+            spawn_count.unwrap(),
+            &entrypoints.unwrap(),
+            enemy_init,
+        );
+
+        Ok((spawner, env))
+    }
+
+    /// env の入力欄だけを検証する (ステージの実行用)。
+    pub fn build_env(&self) -> Result<PlayEnv, BuildErrors> {
+        let mut errors = BuildErrors::default();
+        match self.check_env(&mut errors) {
+            Some(env) => Ok(env),
+            None => Err(errors),
+        }
+    }
+
+    fn check_env(&self, errors: &mut BuildErrors) -> Option<PlayEnv> {
+        let stage = check!(errors, FieldId::Stage, stage_parse(&self.stage));
+        let rank = check!(errors, FieldId::Rank, rank_parse(&self.rank));
+        let rng_idx = check!(
+            errors,
+            FieldId::RngIdx,
+            parse_int::parse::<u8>(&self.rng_idx)
+        );
+        let rng_mode = check!(errors, FieldId::RngPin, rng_mode_parse(&self.rng_pin));
+
+        Some(PlayEnv {
+            second_round: self.second_round,
+            stage: stage?,
+            rng_idx: rng_idx?,
+            rng_mode: rng_mode?,
+            rank: rank?,
+            rank_mode: if self.experimental_rank {
+                RankMode::Experimental
+            } else {
+                RankMode::Fixed
+            },
+        })
+    }
+}
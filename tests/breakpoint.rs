@@ -0,0 +1,59 @@
+//! ブレークポイントのトラップの挿入を確かめる。
+
+use starsoldier_bytecode_playground as playground;
+
+fn spawner(program: &[u8]) -> playground::EnemySpawner {
+    let enemy_init = playground::EnemyInit {
+        sprite_idx_base: 0,
+        program: program.to_vec(),
+        boss: false,
+        difficulty: 0,
+        shot_with_rank: false,
+        accel_shot_with_rank: false,
+        homing_shot_with_rank: false,
+        extra_act_with_rank: false,
+        accel_with_rank: false,
+        rank: 0,
+        x: 0x80,
+        y: 0x20,
+    };
+
+    playground::EnemySpawner::new(0, 1, &[0], enemy_init)
+}
+
+fn instrument(assembly: &str) -> eyre::Result<playground::EnemySpawner> {
+    let srcmap = playground::SourceMap::build(assembly)?;
+    let mut spawner = spawner(srcmap.program());
+
+    let mut breakpoints = playground::BreakpointSet::default();
+    breakpoints.add(playground::Breakpoint {
+        line: 1,
+        condition: None,
+    });
+    breakpoints.instrument(&srcmap, &mut spawner)?;
+
+    Ok(spawner)
+}
+
+#[test]
+fn numeric_branch_target_is_rejected() {
+    assert!(instrument("L00:\n        move 0x19\n        jump 0\n").is_err());
+    assert!(instrument("L00:\n        move 0x19\n        jump L00\n").is_ok());
+}
+
+#[test]
+fn traps_keep_original_program() {
+    let assembly = "L00:\n        move 0x19\n        jump L00\n";
+    let program = playground::SourceMap::build(assembly)
+        .unwrap()
+        .program()
+        .to_vec();
+    let spawner = instrument(assembly).unwrap();
+
+    // 元のプログラムとエントリポイントはそのままで、保存しても残らない。
+    assert_eq!(spawner.program(), program.as_slice());
+    assert_eq!(spawner.entrypoints(), &[0]);
+    let saved = serde_json::to_string(&spawner).unwrap();
+    let mut loaded: playground::EnemySpawner = serde_json::from_str(&saved).unwrap();
+    assert!(!loaded.step(0).unwrap().has_traps());
+}
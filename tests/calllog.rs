@@ -0,0 +1,40 @@
+//! 呼び出しログの絞り込みと、保持件数の上限を確かめる。
+
+use starsoldier_bytecode_playground as playground;
+
+use playground::{CallFilter, CallLog, GameCall, GameCallKind};
+
+fn call(frame: usize, spawn_idx: usize, kind: GameCallKind) -> GameCall {
+    GameCall {
+        frame,
+        spawn_idx,
+        kind,
+    }
+}
+
+#[test]
+fn filter_matches_exact_fields() {
+    let rand = GameCallKind::Rand { rng_idx: 0, ret: 0 };
+    let filter = CallFilter::parse("#3 rand").unwrap();
+
+    assert!(filter.matches(&call(0, 3, rand)));
+    assert!(!filter.matches(&call(0, 30, rand)));
+    assert!(!filter.matches(&call(0, 3, GameCallKind::HeroX { ret: 3 })));
+
+    assert!(CallFilter::parse("").unwrap().matches(&call(0, 30, rand)));
+    assert!(CallFilter::parse("ran").is_err());
+}
+
+#[test]
+fn log_keeps_latest_calls() {
+    let mut log = CallLog::default();
+    for frame in 0..playground::CALL_LOG_MAX + 10 {
+        let rng_idx = frame as u8;
+        log.push(call(frame, 0, GameCallKind::Rand { rng_idx, ret: 0 }));
+    }
+
+    assert_eq!(log.calls().len(), playground::CALL_LOG_MAX);
+    assert_eq!(log.calls().front().unwrap().frame, 10);
+    assert_eq!(log.rand_count(), playground::CALL_LOG_MAX + 10);
+    assert!((0..playground::RNG_TABLE_LEN).all(|idx| log.is_rng_consumed(idx)));
+}
@@ -0,0 +1,109 @@
+//! 全 preset の軌跡を golden ファイルと比較する。
+//!
+//! golden ファイルを再生成するには `UPDATE_GOLDEN=1 cargo test --test golden -- --include-ignored` とする。
+//! golden ファイルを書き込むのはこのときだけで、存在しない golden ファイルはテスト失敗とする。
+//!
+//! golden ファイルはまだ生成・コミットしていないので、それまでは `#[ignore]` にしておく
+//! (生成後に `#[ignore]` を外す)。
+
+use std::path::{Path, PathBuf};
+
+use starsoldier_bytecode_playground as playground;
+
+const FRAME_COUNT: usize = 600;
+
+/// (stage, rank, second_round, rng_idx)
+const ENVS: &[(u8, u8, bool, u8)] = &[
+    (1, 0, false, 0x00),
+    (4, 7, false, 0x40),
+    (16, 3, true, 0x7F),
+];
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn update_mode() -> bool {
+    std::env::var_os("UPDATE_GOLDEN").is_some_and(|v| v != "0")
+}
+
+fn run_trace(
+    preset: &playground::EnemyGroupPreset,
+    (stage, rank, second_round, rng_idx): (u8, u8, bool, u8),
+) -> eyre::Result<String> {
+    let mut sim = playground::Simulation::new(playground::Game {
+        second_round,
+        stage,
+        rng_idx,
+        ..Default::default()
+    });
+    sim.reset(Some(preset.spawner(rank)?));
+
+    let mut tracer = playground::TraceRecorder::new();
+    for _ in 0..FRAME_COUNT {
+        sim.step()?;
+        tracer.record(&sim);
+    }
+
+    let mut buf = vec![];
+    tracer.write_csv(&mut buf)?;
+
+    Ok(String::from_utf8(buf)?)
+}
+
+#[test]
+#[ignore = "tests/golden/*.csv are not generated yet (run with UPDATE_GOLDEN=1 against starsoldier-bytecode)"]
+fn golden_traces() -> eyre::Result<()> {
+    let dir = golden_dir();
+    if update_mode() {
+        std::fs::create_dir_all(&dir)?;
+    }
+
+    let mut mismatches = vec![];
+    let mut missing = vec![];
+
+    for preset in playground::ENEMY_GROUP_PRESETS.iter() {
+        for &env in ENVS {
+            let (stage, rank, second_round, rng_idx) = env;
+            let name = format!(
+                "{:02X}-stage{:02}-rank{}-round{}-rng{:02X}.csv",
+                preset.id,
+                stage,
+                rank,
+                if second_round { 2 } else { 1 },
+                rng_idx
+            );
+            let path = dir.join(&name);
+
+            let actual = run_trace(preset, env)?;
+
+            if update_mode() {
+                std::fs::write(&path, &actual)?;
+                eprintln!("golden written: {}", name);
+                continue;
+            }
+            if !path.exists() {
+                missing.push(name);
+                continue;
+            }
+
+            let expected = std::fs::read_to_string(&path)?;
+            if actual != expected {
+                mismatches.push(name);
+            }
+        }
+    }
+
+    assert!(
+        missing.is_empty(),
+        "golden file missing (generate with UPDATE_GOLDEN=1): {:?}",
+        missing
+    );
+    assert!(
+        mismatches.is_empty(),
+        "trace mismatch (rerun with UPDATE_GOLDEN=1 if intended): {:?}",
+        mismatches
+    );
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! ユーザー preset の id の検査を確かめる。
+
+use starsoldier_bytecode_playground as playground;
+
+fn preset_toml(id: Option<u8>) -> String {
+    let id = id.map(|id| format!("id = {}\n", id)).unwrap_or_default();
+    format!(
+        r#"{}name = "test"
+spawn_interval = 8
+spawn_count = 1
+sprite_idx_base = 0
+boss = false
+difficulty = 0
+shot_with_rank = false
+accel_shot_with_rank = false
+homing_shot_with_rank = false
+extra_act_with_rank = false
+accel_with_rank = false
+x_ini = 0
+y_ini = 0
+entrypoints = ["L00"]
+assembly = "L00:\n        jump L00\n"
+"#,
+        id
+    )
+}
+
+#[test]
+fn user_preset_ids() {
+    let dir = std::env::temp_dir().join("ssbc-user-preset-ids");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.toml"), preset_toml(Some(0x80))).unwrap();
+    std::fs::write(dir.join("b.toml"), preset_toml(Some(0x80))).unwrap();
+    std::fs::write(dir.join("c.toml"), preset_toml(None)).unwrap();
+    std::fs::write(dir.join("d.toml"), preset_toml(Some(0x81))).unwrap();
+
+    let results: Vec<_> = playground::load_user_presets(&dir)
+        .into_iter()
+        .map(|(_, preset)| preset.map(|preset| preset.id))
+        .collect();
+
+    assert_eq!(results[0].as_ref().unwrap(), &0x80);
+    let e = results[1].as_ref().unwrap_err();
+    assert!(e.to_string().contains("duplicate preset id"), "{}", e);
+    assert!(results[2].is_err(), "id is required");
+    assert_eq!(results[3].as_ref().unwrap(), &0x81);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,29 @@
+//! `RngMode` の読み込みと、sweep の軌跡の分類を確かめる。
+
+use serde::Deserialize;
+use starsoldier_bytecode_playground as playground;
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    mode: playground::RngMode,
+}
+
+#[test]
+fn empty_sequence_is_rejected() {
+    let config: Config = toml::from_str("mode = { Sequence = [1, 2] }").unwrap();
+    let seq = playground::RngSequence::new(vec![1, 2]).unwrap();
+    assert_eq!(config.mode, playground::RngMode::Sequence(seq));
+
+    assert!(toml::from_str::<Config>("mode = { Sequence = [] }").is_err());
+    assert!(playground::RngSequence::new(vec![]).is_err());
+}
+
+#[test]
+fn sweep_without_enemies_has_single_path() {
+    let results = playground::rng_sweep(&playground::Game::default(), &[], 60);
+
+    assert_eq!(results.len(), playground::RNG_TABLE_LEN);
+    assert!(results
+        .iter()
+        .all(|r| r.path_class == 0 && r.first_diff_frame.is_none()));
+}
@@ -0,0 +1,74 @@
+//! asset/bytecode-XX.asm をアセンブルし、ROM から抽出した asset/bytecode-XX.bin と比較する。
+//! .bin はユーザーが用意するもので、リポジトリには含まれないので、この比較は
+//! `cargo test -- --ignored` で明示的に実行する (.bin が 1 つもなければ失敗する)。
+//! また、逆アセンブルして再アセンブルすると元のバイト列に戻ることを確かめる。
+
+use std::path::{Path, PathBuf};
+
+use starsoldier_bytecode as bytecode;
+
+use starsoldier_bytecode_playground as playground;
+
+fn asm_paths() -> eyre::Result<Vec<PathBuf>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("asset");
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "asm"))
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+#[test]
+#[ignore = "needs asset/bytecode-XX.bin extracted from the ROM"]
+fn asm_matches_bin() -> eyre::Result<()> {
+    let mut compared = 0;
+    let mut mismatches = vec![];
+
+    for asm_path in asm_paths()? {
+        let bin_path = asm_path.with_extension("bin");
+        if !bin_path.exists() {
+            continue;
+        }
+        compared += 1;
+
+        let program = bytecode::asm(std::fs::read(&asm_path)?.as_slice())?;
+        let expected = std::fs::read(&bin_path)?;
+        if program != expected {
+            mismatches.push(format!(
+                "{}: {}",
+                asm_path.display(),
+                playground::first_diff(&expected, &program)
+            ));
+        }
+    }
+
+    assert!(compared > 0, "no asset/bytecode-XX.bin found");
+    assert!(
+        mismatches.is_empty(),
+        "mismatch:\n{}",
+        mismatches.join("\n")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn disasm_roundtrip() -> eyre::Result<()> {
+    let disassembler = playground::Disassembler::new()?;
+
+    let mut failures = vec![];
+
+    for asm_path in asm_paths()? {
+        let program = bytecode::asm(std::fs::read(&asm_path)?.as_slice())?;
+        if let Err(e) = disassembler.roundtrip(&program) {
+            failures.push(format!("{}: {}", asm_path.display(), e));
+        }
+    }
+
+    assert!(failures.is_empty(), "failure:\n{}", failures.join("\n"));
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! 古いバージョンのセーブステートを読み込めることを確かめる。
+
+use starsoldier_bytecode_playground as playground;
+
+fn spawner() -> playground::EnemySpawner {
+    let enemy_init = playground::EnemyInit {
+        sprite_idx_base: 0x10,
+        program: vec![0x00],
+        boss: false,
+        difficulty: 1,
+        shot_with_rank: false,
+        accel_shot_with_rank: false,
+        homing_shot_with_rank: false,
+        extra_act_with_rank: false,
+        accel_with_rank: false,
+        rank: 0,
+        x: 64,
+        y: 0,
+    };
+
+    playground::EnemySpawner::new(8, 2, &[0, 0], enemy_init)
+}
+
+/// v1 の形式 (`timeline.spawner` が 1 つ) で書き出す。
+fn save_v1(spawner: Option<&playground::EnemySpawner>, path: &std::path::Path) {
+    let mut sim = playground::Simulation::new(playground::Game::default());
+    sim.reset_stage(vec![]);
+    let timeline = playground::Timeline::new(&sim);
+    let state = playground::SaveState::new(playground::EditorFields::default(), timeline, 0);
+
+    let mut value = serde_json::to_value(&state).unwrap();
+    value["version"] = 1.into();
+    let timeline = value["timeline"].as_object_mut().unwrap();
+    timeline.remove("spawners");
+    timeline.insert("spawner".to_owned(), serde_json::to_value(spawner).unwrap());
+
+    std::fs::write(path, serde_json::to_string(&value).unwrap()).unwrap();
+}
+
+#[test]
+fn load_v1_spawner() {
+    let path = std::env::temp_dir().join("ssbc-savestate-v1.json");
+    let spawner = spawner();
+    save_v1(Some(&spawner), &path);
+
+    let state = playground::SaveState::load(&path).unwrap();
+    assert_eq!(state.version, playground::SAVE_STATE_VERSION);
+    let spawners = state.timeline.spawners();
+    assert_eq!(spawners.len(), 1);
+    assert_eq!(spawners[0].frame, 0);
+    assert_eq!(
+        serde_json::to_value(&spawners[0].spawner).unwrap(),
+        serde_json::to_value(&spawner).unwrap()
+    );
+
+    save_v1(None, &path);
+    let state = playground::SaveState::load(&path).unwrap();
+    assert!(state.timeline.spawners().is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,39 @@
+//! `Simulation::reset_stage` が前回の実行の状態を持ち越さないことを確かめる。
+
+use starsoldier_bytecode_playground as playground;
+
+#[test]
+fn reset_stage_restores_hero_and_music() {
+    let mut sim = playground::Simulation::new(playground::Game::default());
+    sim.reset_stage(vec![]);
+
+    let input = playground::HeroInput {
+        left: true,
+        up: true,
+        shoot: true,
+        ..Default::default()
+    };
+    for _ in 0..30 {
+        sim.step_with_input(input).unwrap();
+    }
+    sim.game_mut().music = Some(9);
+    assert_ne!(
+        (sim.game().hero.x, sim.game().hero.y),
+        (playground::HERO_X_INI, playground::HERO_Y_INI)
+    );
+
+    sim.reset_stage(vec![]);
+    let game = sim.game();
+    assert_eq!(
+        (game.hero.x, game.hero.y),
+        (playground::HERO_X_INI, playground::HERO_Y_INI)
+    );
+    assert_eq!(game.hero.death_count(), 0);
+    assert_eq!(game.music, None);
+    assert!(game.shots.is_empty());
+
+    // 再実行の起点もリセット後の状態になる。
+    let timeline = playground::Timeline::new(&sim);
+    assert_eq!(timeline.game().music, None);
+    assert_eq!(timeline.game().hero.x, playground::HERO_X_INI);
+}
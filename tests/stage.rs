@@ -0,0 +1,88 @@
+//! ステージスケジュールの TOML の読み込みを確かめる。
+
+use starsoldier_bytecode_playground as playground;
+
+use playground::{Entrypoint, StageSchedule, StageTrigger};
+
+/// `StageSchedule` の doc comment の例。
+const EXAMPLE: &str = r#"
+name = "stage 1"
+scroll_speed = 1.0
+
+[[groups]]
+frame = 0
+preset = 0x01
+
+[[groups]]
+scroll = 480
+preset = 0x05
+x = 64
+entrypoints = ["L07", "L3D"]
+spawn_count = 2
+"#;
+
+#[test]
+fn parse_doc_example() {
+    let schedule = StageSchedule::parse(EXAMPLE).unwrap();
+
+    assert_eq!(schedule.name, "stage 1");
+    assert_eq!(schedule.groups.len(), 2);
+
+    let (first, second) = (&schedule.groups[0], &schedule.groups[1]);
+    assert!(matches!(first.at, StageTrigger::Frame(0)));
+    assert_eq!(first.preset, 0x01);
+    assert!(first.overrides.entrypoints.is_none());
+
+    assert!(matches!(second.at, StageTrigger::Scroll(480)));
+    assert_eq!(second.at.frame(schedule.scroll_speed), 480);
+    assert_eq!(second.preset, 0x05);
+    assert_eq!(second.overrides.x, Some(64));
+    assert_eq!(second.overrides.spawn_count, Some(2));
+    assert_eq!(
+        second.overrides.entrypoints,
+        Some(vec![
+            Entrypoint::Label("L07".to_owned()),
+            Entrypoint::Label("L3D".to_owned()),
+        ])
+    );
+}
+
+#[test]
+fn build_doc_example() {
+    let presets: Vec<_> = playground::ENEMY_GROUP_PRESETS
+        .iter()
+        .map(playground::PresetFile::from_builtin)
+        .collect();
+    let spawners = StageSchedule::parse(EXAMPLE)
+        .unwrap()
+        .build(&presets, 0)
+        .unwrap();
+
+    assert_eq!(spawners.len(), 2);
+    assert_eq!(spawners[0].frame, 0);
+    assert_eq!(spawners[1].frame, 480);
+
+    let deda = presets.iter().find(|preset| preset.id == 0x05).unwrap();
+    let entrypoints = playground::entrypoints_resolve(
+        &[
+            Entrypoint::Label("L07".to_owned()),
+            Entrypoint::Label("L3D".to_owned()),
+        ],
+        &deda.assembly,
+    )
+    .unwrap();
+    assert_eq!(spawners[1].spawner.entrypoints(), entrypoints.as_slice());
+}
+
+#[test]
+fn reject_unknown_group_key() {
+    let text = EXAMPLE.replace("spawn_count", "spawn_cuont");
+    let e = StageSchedule::parse(&text).unwrap_err();
+    assert!(e.to_string().contains("spawn_cuont"), "{}", e);
+}
+
+#[test]
+fn reject_ambiguous_trigger() {
+    let text = EXAMPLE.replace("frame = 0", "frame = 0\nscroll = 16");
+    assert!(StageSchedule::parse(&text).is_err());
+}
@@ -0,0 +1,52 @@
+//! チェックポイントからの seek が、最初から実行した場合と同じ状態になることを確かめる。
+//! 敵を出さないので、チェックポイントは `CHECKPOINT_INTERVAL` ごとに取られる。
+
+use starsoldier_bytecode_playground as playground;
+
+fn input(frame: usize) -> playground::HeroInput {
+    playground::HeroInput {
+        left: frame % 90 < 30,
+        right: frame % 90 >= 60,
+        up: frame % 50 < 10,
+        down: frame % 50 >= 40,
+        shoot: frame % 7 < 3,
+    }
+}
+
+fn run(frames: usize) -> playground::Simulation {
+    let mut sim = playground::Simulation::new(playground::Game::default());
+    sim.reset_stage(vec![]);
+    for frame in 0..frames {
+        sim.step_with_input(input(frame)).unwrap();
+    }
+
+    sim
+}
+
+#[test]
+fn seek_from_checkpoint() {
+    let frames = playground::CHECKPOINT_INTERVAL * 3 + 17;
+
+    let mut sim = playground::Simulation::new(playground::Game::default());
+    sim.reset_stage(vec![]);
+    let mut timeline = playground::Timeline::new(&sim);
+    for frame in 0..frames {
+        sim.step_with_input(input(frame)).unwrap();
+        timeline.record_step(input(frame), &sim);
+    }
+
+    for &frame in &[0, 1, playground::CHECKPOINT_INTERVAL, frames - 5, frames] {
+        let sought = timeline.seek(frame).unwrap();
+        let expected = run(frame);
+
+        assert_eq!(sought.frame_count(), expected.frame_count());
+        let (a, b) = (&sought.game().hero, &expected.game().hero);
+        assert_eq!((a.x, a.y), (b.x, b.y), "hero position at frame {}", frame);
+        assert_eq!(
+            sought.game().shots.len(),
+            expected.game().shots.len(),
+            "shot count at frame {}",
+            frame
+        );
+    }
+}